@@ -0,0 +1,53 @@
+use std::fs;
+use std::path::Path;
+
+use crate::cli_args::CliArgs;
+use crate::config_file::{load_config, validate_config};
+
+/// Validates the config file without starting the app and returns the process exit code.
+///
+/// Every problem is printed as `file:line:column: message`, so the output can be consumed by
+/// editors and CI logs.
+pub fn check_config(cli_args: &CliArgs) -> i32 {
+    let Some(config_file) = cli_args.config_file_or_default() else {
+        eprintln!("Could not determine the config directory");
+        return 2;
+    };
+    if !config_file.exists() {
+        eprintln!("{}: config file does not exist", config_file.display());
+        return 2;
+    }
+    let config = match load_config(&config_file) {
+        Ok(config) => config,
+        Err(error) => {
+            print_problem(&config_file, error.location(), &error.to_string());
+            return 1;
+        }
+    };
+    let source = fs::read_to_string(&config_file).unwrap_or_default();
+    let problems = validate_config(&config, &source);
+    for problem in &problems {
+        let location = problem.line.zip(problem.column);
+        print_problem(&config_file, location, &problem.message);
+    }
+    if problems.is_empty() {
+        println!("{}: OK", config_file.display());
+        0
+    } else {
+        eprintln!(
+            "{}: {} problem(s) found",
+            config_file.display(),
+            problems.len()
+        );
+        1
+    }
+}
+
+fn print_problem(config_file: &Path, location: Option<(usize, usize)>, message: &str) {
+    match location {
+        Some((line, column)) => {
+            eprintln!("{}:{}:{}: {}", config_file.display(), line, column, message)
+        }
+        None => eprintln!("{}: {}", config_file.display(), message),
+    }
+}
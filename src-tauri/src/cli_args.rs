@@ -1,6 +1,8 @@
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
 use std::path::PathBuf;
 
+use crate::config_file::default_config_path;
+
 #[derive(Debug, Clone, Parser)]
 #[command(name = "pmon", about = "GitHub Workflow Monitor")]
 pub struct CliArgs {
@@ -17,6 +19,37 @@ pub struct CliArgs {
         help = "Use an alternative config file"
     )]
     pub(crate) config_file: Option<PathBuf>,
+    #[arg(
+        long = "check-config",
+        help = "Validate the config file, print all problems and exit"
+    )]
+    pub(crate) check_config: bool,
+    #[arg(
+        long = "print-schema",
+        help = "Print the JSON Schema of the config file and exit"
+    )]
+    pub(crate) print_schema: bool,
+    #[command(subcommand)]
+    pub(crate) command: Option<Command>,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// Poll every repo once, print its status and exit
+    Status(StatusArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct StatusArgs {
+    #[arg(long = "json", help = "Print the statuses as JSON")]
+    pub(crate) json: bool,
+}
+
+impl CliArgs {
+    /// The config file given on the command line, or the default one.
+    pub fn config_file_or_default(&self) -> Option<PathBuf> {
+        self.config_file.clone().or_else(default_config_path)
+    }
 }
 
 pub fn load_cli_args() -> CliArgs {
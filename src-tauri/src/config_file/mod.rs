@@ -1,22 +1,81 @@
+mod validate;
+
 use crate::core::config::{create_default_config, Config};
-use std::path::Path;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 use std::{fmt, fs};
 
+pub use validate::validate_config;
+
+/// The config file used when none is given on the command line.
+pub fn default_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|config_dir| config_dir.join("config.yaml"))
+}
+
+/// Where the JSON Schema of the config file is kept up to date, in the app's own directory.
+fn schema_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|config_dir| config_dir.join("pmon").join("config.schema.json"))
+}
+
+/// Writes the schema of this version, so the editor header of existing configs stays current.
+pub fn write_schema() -> Result<Option<PathBuf>, ConfigError> {
+    let Some(schema_file) = schema_path() else {
+        return Ok(None);
+    };
+    write_schema_file(&schema_file)?;
+    Ok(Some(schema_file))
+}
+
+fn write_schema_file(schema_file: &Path) -> Result<(), ConfigError> {
+    let schema = config_schema();
+    if fs::read_to_string(schema_file).is_ok_and(|existing| existing == schema) {
+        return Ok(());
+    }
+    if let Some(dir) = schema_file.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(schema_file, schema)?;
+    Ok(())
+}
+
+/// The JSON Schema of the config file, for editor completion and validation.
+pub fn config_schema() -> String {
+    let schema = schemars::schema_for!(Config);
+    serde_json::to_string_pretty(&schema).expect("JSON Schema is always serializable")
+}
+
+/// Reads the config, writing a default config first if the file does not exist yet.
 pub fn read_config(path: impl AsRef<Path>) -> Result<Config, ConfigError> {
     if !fs::exists(path.as_ref())? {
-        write_config(&path, &create_default_config())?
+        write_default_config(path.as_ref())?
     }
+    load_config(path)
+}
+
+/// Reads an existing config without creating any files.
+pub fn load_config(path: impl AsRef<Path>) -> Result<Config, ConfigError> {
     log::info!("Reading config from {}", path.as_ref().display());
     let contents = fs::read_to_string(path)?;
-    let raw_config: serde_yaml::Value = serde_yaml::from_str(&contents)?;
-    let config = serde_yaml::from_value(raw_config)?;
+    // Deserialize directly from the source, so that errors carry their line and column.
+    let config = serde_yaml::from_str(&contents)?;
     log::debug!("Config: {config:?}");
     Ok(config)
 }
 
-pub fn write_config(path: impl AsRef<Path>, config: &Config) -> Result<(), ConfigError> {
-    let raw_config = serde_yaml::to_value(config)?;
-    fs::write(path, serde_yaml::to_string(&raw_config)?)?;
+/// Writes the default config with a header that points yaml-language-server at the schema.
+fn write_default_config(path: &Path) -> Result<(), ConfigError> {
+    let header = match write_schema()? {
+        Some(schema_file) => format!(
+            "# yaml-language-server: $schema={}\n",
+            schema_file.display()
+        ),
+        None => String::new(),
+    };
+    let raw_config = serde_yaml::to_value(create_default_config())?;
+    fs::write(
+        path,
+        format!("{}{}", header, serde_yaml::to_string(&raw_config)?),
+    )?;
     Ok(())
 }
 
@@ -26,6 +85,37 @@ pub enum ConfigError {
     Parse(serde_yaml::Error),
 }
 
+impl ConfigError {
+    /// Line and column (both 1-based) of a parse error, if known.
+    pub fn location(&self) -> Option<(usize, usize)> {
+        match self {
+            ConfigError::Io(_) => None,
+            ConfigError::Parse(err) => err
+                .location()
+                .map(|location| (location.line(), location.column())),
+        }
+    }
+}
+
+/// A serializable summary of a [`ConfigError`] for display in the UI.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigErrorReport {
+    pub message: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+impl From<&ConfigError> for ConfigErrorReport {
+    fn from(error: &ConfigError) -> Self {
+        let location = error.location();
+        Self {
+            message: error.to_string(),
+            line: location.map(|(line, _)| line),
+            column: location.map(|(_, column)| column),
+        }
+    }
+}
+
 impl fmt::Display for ConfigError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -55,3 +145,58 @@ impl From<serde_yaml::Error> for ConfigError {
         Self::Parse(error)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ConfigError, ConfigErrorReport};
+    use crate::core::config::Config;
+
+    #[test]
+    fn parse_errors_report_line_and_column() {
+        let error = serde_yaml::from_str::<Config>("providers:\n  - type: bitbucket\n")
+            .map_err(ConfigError::from)
+            .unwrap_err();
+
+        let report = ConfigErrorReport::from(&error);
+
+        assert_eq!(report.line, Some(2));
+        assert!(report.column.is_some());
+    }
+
+    #[test]
+    fn schema_file_is_rewritten_when_outdated() {
+        let schema_file = std::env::temp_dir()
+            .join(format!("pmon-schema-test-{}", std::process::id()))
+            .join("config.schema.json");
+        super::write_schema_file(&schema_file).unwrap();
+        std::fs::write(&schema_file, "{}").unwrap();
+
+        super::write_schema_file(&schema_file).unwrap();
+
+        let written = std::fs::read_to_string(&schema_file).unwrap();
+        assert_eq!(written, super::config_schema());
+        let _ = std::fs::remove_dir_all(schema_file.parent().unwrap());
+    }
+
+    #[test]
+    fn loading_a_missing_config_creates_nothing() {
+        let config_file = std::env::temp_dir()
+            .join(format!("pmon-load-test-{}", std::process::id()))
+            .join("config.yaml");
+
+        let error = super::load_config(&config_file).unwrap_err();
+
+        assert!(matches!(error, ConfigError::Io(_)));
+        assert!(!config_file.exists());
+    }
+
+    #[test]
+    fn schema_describes_provider_types() {
+        let schema: serde_json::Value = serde_json::from_str(&super::config_schema()).unwrap();
+
+        let schema = schema.to_string();
+        assert!(schema.contains("\"github\""));
+        assert!(schema.contains("\"gitlab\""));
+        assert!(schema.contains("main_branch"));
+    }
+}
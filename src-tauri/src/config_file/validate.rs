@@ -0,0 +1,542 @@
+use std::fmt;
+use std::path::Path;
+
+use crate::core::config::{Config, GithubMode, ProviderConfig, TokenConfig, WorkflowSelection};
+use crate::data_providers::{expand_env, http_client, json_pointer};
+
+/// A semantic problem in an otherwise parseable config file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigProblem {
+    pub message: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "{}:{}: {}", line, column, self.message),
+            _ => write!(f, "{}", self.message),
+        }
+    }
+}
+
+type Location = Option<(usize, usize)>;
+/// Provider, host, repo, branch and workflows of a monitored pipeline.
+type RepoKey<'a> = (&'a str, &'a str, &'a str, &'a str, String);
+
+/// Checks everything that deserializing the config does not catch.
+///
+/// `source` is the text the config was parsed from and is only used to find the position
+/// of a problem in the file.
+pub fn validate_config(config: &Config, source: &str) -> Vec<ConfigProblem> {
+    let mut validator = Validator {
+        source,
+        visited: Vec::new(),
+        problems: Vec::new(),
+    };
+    let mut seen_repos: Vec<RepoKey> = Vec::new();
+
+    for provider in &config.providers {
+        match provider {
+            ProviderConfig::Github {
+                token,
+                api_base_url,
+                ca_cert,
+                repos,
+            } => {
+                validator.check_token(token);
+                if let Some(api_base_url) = api_base_url {
+                    validator.check_url("GitHub", "api_base_url", api_base_url);
+                }
+                if let Some(ca_cert) = ca_cert {
+                    validator.check_ca_cert(ca_cert);
+                }
+                for repo in repos {
+                    let name = validator.visit("name", &repo.name);
+                    let main_branch = validator.visit("main_branch", &repo.main_branch);
+                    if !is_owner_and_name(&repo.name) {
+                        validator.report(
+                            format!(
+                                "GitHub repo name \"{}\" must have the form owner/name",
+                                repo.name
+                            ),
+                            name,
+                        );
+                    }
+                    validator.check_not_empty("main_branch", &repo.main_branch, main_branch);
+                    let workflows = match &repo.workflow {
+                        Some(WorkflowSelection::One(workflow)) => {
+                            let location = validator.visit("workflow", workflow);
+                            validator.check_not_empty("workflow", workflow, location);
+                            workflow.clone()
+                        }
+                        Some(WorkflowSelection::Many(workflows)) => {
+                            if workflows.is_empty() {
+                                validator.report(
+                                    "workflow must not be an empty list, omit it to monitor all workflows"
+                                        .to_string(),
+                                    name,
+                                );
+                            }
+                            if workflows.iter().any(|workflow| workflow.trim().is_empty()) {
+                                validator.report(
+                                    "workflow must not contain empty entries".to_string(),
+                                    name,
+                                );
+                            }
+                            workflows.join(",")
+                        }
+                        None => String::new(),
+                    };
+                    if repo.mode == GithubMode::Checks && repo.workflow.is_some() {
+                        validator.report("workflow is ignored in checks mode".to_string(), name);
+                    }
+                    let key = (
+                        "github",
+                        host(api_base_url.as_deref()),
+                        &*repo.name,
+                        &*repo.main_branch,
+                        workflows,
+                    );
+                    validator.check_duplicate(&mut seen_repos, key, name);
+                }
+            }
+            ProviderConfig::Gitlab {
+                url,
+                token,
+                ca_cert,
+                repos,
+                ..
+            } => {
+                if let Some(url) = url {
+                    validator.check_url("GitLab", "url", url);
+                }
+                validator.check_token(token);
+                if let Some(ca_cert) = ca_cert {
+                    validator.check_ca_cert(ca_cert);
+                }
+                for repo in repos {
+                    let name = validator.visit("name", &repo.name);
+                    let main_branch = validator.visit("main_branch", &repo.main_branch);
+                    validator.check_not_empty("name", &repo.name, name);
+                    validator.check_not_empty("main_branch", &repo.main_branch, main_branch);
+                    let key = (
+                        "gitlab",
+                        host(url.as_deref()),
+                        &*repo.name,
+                        &*repo.main_branch,
+                        String::new(),
+                    );
+                    validator.check_duplicate(&mut seen_repos, key, name);
+                }
+            }
+            ProviderConfig::Gitea { url, token, repos } => {
+                validator.check_url("Gitea", "url", url);
+                validator.check_token(token);
+                for repo in repos {
+                    let name = validator.visit("name", &repo.name);
+                    let main_branch = validator.visit("main_branch", &repo.main_branch);
+                    let workflow = repo.workflow.as_deref().unwrap_or_default();
+                    if let Some(workflow) = &repo.workflow {
+                        let location = validator.visit("workflow", workflow);
+                        validator.check_not_empty("workflow", workflow, location);
+                    }
+                    if !is_owner_and_name(&repo.name) {
+                        validator.report(
+                            format!(
+                                "Gitea repo name \"{}\" must have the form owner/name",
+                                repo.name
+                            ),
+                            name,
+                        );
+                    }
+                    validator.check_not_empty("main_branch", &repo.main_branch, main_branch);
+                    let key = (
+                        "gitea",
+                        host(Some(url)),
+                        &*repo.name,
+                        &*repo.main_branch,
+                        workflow.to_string(),
+                    );
+                    validator.check_duplicate(&mut seen_repos, key, name);
+                }
+            }
+            ProviderConfig::Jenkins {
+                url,
+                user,
+                token,
+                jobs,
+            } => {
+                validator.check_url("Jenkins", "url", url);
+                // Jenkins falls back to anonymous access with only one of them.
+                match (user, token) {
+                    (Some(user), Some(token)) => {
+                        validator.check_token(user);
+                        validator.check_token(token);
+                    }
+                    (Some(user), None) => {
+                        let location = validator.check_token(user);
+                        validator.report(
+                            "Jenkins user is set without token, set both or neither".to_string(),
+                            location,
+                        );
+                    }
+                    (None, Some(token)) => {
+                        let location = validator.check_token(token);
+                        validator.report(
+                            "Jenkins token is set without user, set both or neither".to_string(),
+                            location,
+                        );
+                    }
+                    (None, None) => {}
+                }
+                for job in jobs {
+                    let name = validator.visit("name", &job.name);
+                    validator.check_not_empty("name", &job.name, name);
+                    let branch = job.branch.as_deref().unwrap_or_default();
+                    if let Some(branch) = &job.branch {
+                        let location = validator.visit("branch", branch);
+                        validator.check_not_empty("branch", branch, location);
+                    }
+                    let key = (
+                        "jenkins",
+                        host(Some(url)),
+                        &*job.name,
+                        branch,
+                        String::new(),
+                    );
+                    validator.check_duplicate(&mut seen_repos, key, name);
+                }
+            }
+            ProviderConfig::Woodpecker {
+                url, token, repos, ..
+            } => {
+                validator.check_url("Woodpecker", "url", url);
+                validator.check_token(token);
+                for repo in repos {
+                    let name = validator.visit("name", &repo.name);
+                    let main_branch = validator.visit("main_branch", &repo.main_branch);
+                    if !is_owner_and_name(&repo.name) {
+                        validator.report(
+                            format!(
+                                "Woodpecker repo name \"{}\" must have the form owner/name",
+                                repo.name
+                            ),
+                            name,
+                        );
+                    }
+                    validator.check_not_empty("main_branch", &repo.main_branch, main_branch);
+                    let key = (
+                        "woodpecker",
+                        host(Some(url)),
+                        &*repo.name,
+                        &*repo.main_branch,
+                        String::new(),
+                    );
+                    validator.check_duplicate(&mut seen_repos, key, name);
+                }
+            }
+            ProviderConfig::Http { endpoints } => {
+                for endpoint in endpoints {
+                    let name = validator.visit("name", &endpoint.name);
+                    validator.check_not_empty("name", &endpoint.name, name);
+                    validator.check_url("HTTP endpoint", "url", &endpoint.url);
+                    for header in &endpoint.headers {
+                        let location = validator.visit("value", &header.value);
+                        if let Err(error) = expand_env(&header.value) {
+                            validator.report(error, location);
+                        }
+                    }
+                    let status = validator.visit("status", &endpoint.status);
+                    if let Err(error) = json_pointer(&endpoint.status) {
+                        validator.report(error, status);
+                    }
+                    if endpoint.states.is_empty() {
+                        validator.report("states must not be empty".to_string(), name);
+                    }
+                    let key = ("http", "", &*endpoint.name, "", String::new());
+                    validator.check_duplicate(&mut seen_repos, key, name);
+                }
+            }
+            ProviderConfig::Command { commands } => {
+                for command in commands {
+                    let name = validator.visit("name", &command.name);
+                    validator.check_not_empty("name", &command.name, name);
+                    let location = validator.visit("command", &command.command);
+                    validator.check_not_empty("command", &command.command, location);
+                    if command.timeout_seconds == 0 {
+                        validator.report("timeout_seconds must be positive".to_string(), name);
+                    }
+                    let key = ("command", "", &*command.name, "", String::new());
+                    validator.check_duplicate(&mut seen_repos, key, name);
+                }
+            }
+        }
+    }
+
+    validator.problems
+}
+
+struct Validator<'a> {
+    source: &'a str,
+    visited: Vec<(&'static str, &'a str)>,
+    problems: Vec<ConfigProblem>,
+}
+
+impl<'a> Validator<'a> {
+    /// Locates the next `key: value` pair in the source. Values must be visited in the order in
+    /// which they appear in the file, so that repeated pairs are told apart.
+    fn visit(&mut self, key: &'static str, value: &'a str) -> Location {
+        let occurrence = self
+            .visited
+            .iter()
+            .filter(|visited| **visited == (key, value))
+            .count();
+        self.visited.push((key, value));
+        locate(self.source, key, value, occurrence)
+    }
+
+    fn report(&mut self, message: String, location: Location) {
+        self.problems.push(ConfigProblem {
+            message,
+            line: location.map(|(line, _)| line),
+            column: location.map(|(_, column)| column),
+        });
+    }
+
+    fn check_token(&mut self, token: &'a TokenConfig) -> Location {
+        let location = self.visit("env", &token.env);
+        if token.env.trim().is_empty() {
+            self.report("token.env must not be empty".to_string(), location);
+        } else if std::env::var(&token.env).is_err() {
+            self.report(
+                format!("Environment variable {} is not set", token.env),
+                location,
+            );
+        }
+        location
+    }
+
+    fn check_url(&mut self, provider: &str, key: &'static str, url: &'a str) -> Location {
+        let location = self.visit(key, url);
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            self.report(
+                format!(
+                    "{} {} \"{}\" must start with http:// or https://",
+                    provider, key, url
+                ),
+                location,
+            );
+        }
+        location
+    }
+
+    fn check_ca_cert(&mut self, path: &'a Path) {
+        let location = path.to_str().and_then(|value| self.visit("ca_cert", value));
+        if !path.is_file() {
+            self.report(
+                format!("ca_cert {} does not exist", path.display()),
+                location,
+            );
+        } else if let Err(error) = http_client(Some(path), false) {
+            self.report(error, location);
+        }
+    }
+
+    fn check_not_empty(&mut self, key: &str, value: &str, location: Location) {
+        if value.trim().is_empty() {
+            self.report(format!("{} must not be empty", key), location);
+        }
+    }
+
+    fn check_duplicate(
+        &mut self,
+        seen_repos: &mut Vec<RepoKey<'a>>,
+        key: RepoKey<'a>,
+        location: Location,
+    ) {
+        if seen_repos.contains(&key) {
+            self.report(
+                format!(
+                    "Repo {} on branch {} is configured more than once",
+                    key.2, key.3
+                ),
+                location,
+            );
+        }
+        seen_repos.push(key);
+    }
+}
+
+/// The server of a provider, empty for its default, so the same repo may be monitored on two hosts.
+fn host(url: Option<&str>) -> &str {
+    url.unwrap_or_default().trim_end_matches('/')
+}
+
+fn is_owner_and_name(name: &str) -> bool {
+    match name.split_once('/') {
+        Some((owner, repo)) => !owner.is_empty() && !repo.is_empty() && !repo.contains('/'),
+        None => false,
+    }
+}
+
+/// Finds the 1-based line and column of the `occurrence`th `key: value` pair in `source`.
+///
+/// This is a plain text search, which is good enough to point at the offending line of the
+/// small, hand written files pmon reads.
+fn locate(source: &str, key: &str, value: &str, occurrence: usize) -> Location {
+    source
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let content = line.trim_start().trim_start_matches("- ");
+            let column = line.len() - content.len() + 1;
+            let rest = content.strip_prefix(key)?.trim_start().strip_prefix(':')?;
+            let found = unquote(strip_comment(rest).trim());
+            (found == value.trim()).then_some((index + 1, column))
+        })
+        .nth(occurrence)
+}
+
+fn strip_comment(value: &str) -> &str {
+    match value.find(" #") {
+        Some(index) => &value[..index],
+        None => value,
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .or_else(|| {
+            value
+                .strip_prefix('\'')
+                .and_then(|value| value.strip_suffix('\''))
+        })
+        .unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate_config;
+    use crate::core::config::Config;
+
+    const SOURCE: &str = r#"providers:
+  - type: github
+    token:
+      env: PMON_TEST_UNSET_TOKEN
+    repos:
+      - name: just-a-name
+        main_branch: main
+        workflow: ci.yml
+      - name: org/repo
+        main_branch: ""
+        workflow: ci.yml
+      - name: org/repo
+        main_branch: main
+        workflow: ci.yml
+      - name: org/repo
+        main_branch: main
+        workflow: ci.yml
+"#;
+
+    #[test]
+    fn reports_all_problems_with_positions() {
+        let config: Config = serde_yaml::from_str(SOURCE).unwrap();
+
+        let problems: Vec<String> = validate_config(&config, SOURCE)
+            .iter()
+            .map(|problem| problem.to_string())
+            .collect();
+
+        assert_eq!(
+            problems,
+            vec![
+                "4:7: Environment variable PMON_TEST_UNSET_TOKEN is not set",
+                "6:9: GitHub repo name \"just-a-name\" must have the form owner/name",
+                "10:9: main_branch must not be empty",
+                "15:9: Repo org/repo on branch main is configured more than once",
+            ]
+        );
+    }
+
+    #[test]
+    fn allows_same_repo_on_two_hosts_but_not_empty_workflow_lists() {
+        let source = r#"providers:
+  - type: gitlab
+    token:
+      env: PATH
+    repos:
+      - name: org/repo
+        main_branch: main
+  - type: gitlab
+    url: https://gitlab.example.com
+    token:
+      env: PATH
+    repos:
+      - name: org/repo
+        main_branch: main
+  - type: github
+    token:
+      env: PATH
+    repos:
+      - name: org/app
+        main_branch: main
+        workflow: []
+"#;
+        let config: Config = serde_yaml::from_str(source).unwrap();
+
+        let problems: Vec<String> = validate_config(&config, source)
+            .iter()
+            .map(|problem| problem.to_string())
+            .collect();
+
+        assert_eq!(
+            problems,
+            vec!["19:9: workflow must not be an empty list, omit it to monitor all workflows"]
+        );
+    }
+
+    #[test]
+    fn reports_jenkins_user_without_token() {
+        let source = r#"providers:
+  - type: jenkins
+    url: https://jenkins.example.com
+    user:
+      env: PATH
+    jobs:
+      - name: app
+"#;
+        let config: Config = serde_yaml::from_str(source).unwrap();
+
+        let problems: Vec<String> = validate_config(&config, source)
+            .iter()
+            .map(|problem| problem.to_string())
+            .collect();
+
+        assert_eq!(
+            problems,
+            vec!["5:7: Jenkins user is set without token, set both or neither"]
+        );
+    }
+
+    #[test]
+    fn reports_a_ca_cert_that_is_no_certificate() {
+        let ca_cert = std::env::temp_dir().join(format!("pmon-check-{}.pem", std::process::id()));
+        std::fs::write(&ca_cert, "not a certificate").unwrap();
+        let source = format!(
+            "providers:\n  - type: github\n    token:\n      env: PATH\n    ca_cert: {}\n    repos: []\n",
+            ca_cert.display()
+        );
+        let config: Config = serde_yaml::from_str(&source).unwrap();
+
+        let problems = validate_config(&config, &source);
+        let _ = std::fs::remove_file(&ca_cert);
+
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].line, Some(5));
+        assert!(problems[0].message.starts_with("Invalid certificate"));
+    }
+}
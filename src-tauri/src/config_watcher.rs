@@ -0,0 +1,79 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config_file::{read_config, ConfigErrorReport};
+use crate::data_providers::provider_from_config;
+use crate::setup::AppState;
+use crate::tray_icon;
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+const CONFIG_RELOADED_EVENT: &str = "config-reloaded";
+const CONFIG_ERROR_EVENT: &str = "config-error";
+
+/// Polls the modification time of the config file and reloads it whenever it changes.
+pub fn watch(app: AppHandle, config_file: PathBuf) {
+    thread::spawn(move || {
+        let mut last_modified = modified_time(&config_file);
+        loop {
+            thread::sleep(WATCH_INTERVAL);
+            let modified = modified_time(&config_file);
+            if modified.is_none() || modified == last_modified {
+                continue;
+            }
+            last_modified = modified;
+            reload_config(&app, &config_file);
+        }
+    });
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+pub fn reload_config(app: &AppHandle, config_file: &Path) {
+    let state = app.state::<AppState>();
+    let config = match read_config(config_file) {
+        Ok(config) => config,
+        Err(error) => {
+            log::error!("Failed to reload config.yaml: {}", error);
+            set_config_error(app, Some(ConfigErrorReport::from(&error)));
+            return;
+        }
+    };
+    log::info!("Reloaded config from {}", config_file.display());
+    let stale = match state.providers.lock() {
+        Ok(mut providers) => providers.remove_stale(&config.providers),
+        Err(_) => Vec::new(),
+    };
+    // Stopping joins the polling threads, refreshing must not wait for that behind the lock.
+    for mut provider in stale {
+        provider.stop();
+    }
+    if let Ok(mut providers) = state.providers.lock() {
+        providers.start_missing(&config.providers, &state.gateway, provider_from_config);
+    }
+    if let Err(error) = app.emit(CONFIG_RELOADED_EVENT, &config) {
+        eprintln!("Failed to emit reloaded config: {}", error);
+    }
+    if let Ok(mut current) = state.config.lock() {
+        *current = config;
+    }
+    set_config_error(app, None);
+}
+
+pub fn set_config_error(app: &AppHandle, error: Option<ConfigErrorReport>) {
+    let message = error.as_ref().map(|error| error.message.as_str());
+    tray_icon::show_config_error(app, message);
+    if let Err(emit_error) = app.emit(CONFIG_ERROR_EVENT, &error) {
+        eprintln!("Failed to emit config error: {}", emit_error);
+    }
+    if let Ok(mut config_error) = app.state::<AppState>().config_error.lock() {
+        *config_error = error;
+    }
+}
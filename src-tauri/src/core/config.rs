@@ -1,49 +1,312 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize)]
+use crate::core::StateSummary;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
 pub struct Config {
     pub providers: Vec<ProviderConfig>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum ProviderConfig {
+    /// Monitors GitHub Actions workflows.
     Github {
         token: TokenConfig,
+        /// API root URL, e.g. https://github.example.com/api/v3 for GitHub Enterprise Server.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        api_base_url: Option<String>,
+        /// PEM file with an additional CA certificate to trust.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ca_cert: Option<PathBuf>,
         repos: Vec<GithubRepoConfig>,
     },
+    /// Monitors GitLab CI pipelines.
     Gitlab {
+        /// Root URL of a self-hosted instance, e.g. https://gitlab.example.com. Defaults to gitlab.com.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        url: Option<String>,
         token: TokenConfig,
+        /// PEM file with an additional CA certificate to trust.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ca_cert: Option<PathBuf>,
+        /// Accept invalid TLS certificates. Only meant for testing instances.
+        #[serde(default, skip_serializing_if = "is_default")]
+        insecure_skip_verify: bool,
         repos: Vec<GitlabRepoConfig>,
     },
+    /// Monitors Gitea or Forgejo Actions workflows.
+    #[serde(alias = "forgejo")]
+    Gitea {
+        /// Root URL of the instance, e.g. https://codeberg.org.
+        url: String,
+        token: TokenConfig,
+        repos: Vec<GiteaRepoConfig>,
+    },
+    /// Monitors Jenkins jobs.
+    Jenkins {
+        /// Root URL of the Jenkins controller.
+        url: String,
+        /// User name for basic auth, required together with `token`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        user: Option<TokenConfig>,
+        /// API token for basic auth, required together with `user`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token: Option<TokenConfig>,
+        jobs: Vec<JenkinsJobConfig>,
+    },
+    /// Monitors Woodpecker or Drone CI pipelines.
+    Woodpecker {
+        /// Root URL of the server.
+        url: String,
+        #[serde(default)]
+        api: WoodpeckerApi,
+        token: TokenConfig,
+        repos: Vec<WoodpeckerRepoConfig>,
+    },
+    /// Monitors JSON status endpoints of other tools.
+    Http { endpoints: Vec<HttpEndpointConfig> },
+    /// Runs local commands, e.g. test suites or health scripts.
+    Command { commands: Vec<CommandConfig> },
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
 pub struct TokenConfig {
+    /// Name of the environment variable holding the API token.
     pub env: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
 pub struct GithubRepoConfig {
+    /// Repository in the form owner/name.
     pub name: String,
+    /// Branch whose workflow runs are monitored.
     pub main_branch: String,
-    pub workflow: String,
+    /// Workflow file name like ci.yml, a glob like deploy-*.yml or a list of them.
+    /// All workflows of the branch are monitored if omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workflow: Option<WorkflowSelection>,
+    /// Where the state of the branch is read from.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub mode: GithubMode,
+    /// Include the repo in the tray icon state.
     #[serde(default = "default_show_in_tray")]
     pub show_in_tray: bool,
+    #[serde(default)]
+    pub notifications: NotificationConfig,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum GithubMode {
+    /// Runs of GitHub Actions workflows.
+    #[default]
+    Actions,
+    /// Check runs and commit statuses of the head commit, e.g. reported by third-party CI apps.
+    Checks,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(untagged)]
+pub enum WorkflowSelection {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl WorkflowSelection {
+    pub fn patterns(&self) -> Vec<String> {
+        match self {
+            WorkflowSelection::One(workflow) => vec![workflow.clone()],
+            WorkflowSelection::Many(workflows) => workflows.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
 pub struct GitlabRepoConfig {
+    /// Project path, e.g. group/project.
     pub name: String,
+    /// Branch whose pipelines are monitored.
     pub main_branch: String,
+    /// Include the repo in the tray icon state.
     #[serde(default = "default_show_in_tray")]
     pub show_in_tray: bool,
+    #[serde(default)]
+    pub notifications: NotificationConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+pub struct GiteaRepoConfig {
+    /// Repository in the form owner/name.
+    pub name: String,
+    /// Branch whose workflow runs are monitored.
+    pub main_branch: String,
+    /// Workflow file name, e.g. ci.yml. Runs of all workflows are considered if omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workflow: Option<String>,
+    /// Include the repo in the tray icon state.
+    #[serde(default = "default_show_in_tray")]
+    pub show_in_tray: bool,
+    #[serde(default)]
+    pub notifications: NotificationConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+pub struct JenkinsJobConfig {
+    /// Path of the job, with folders separated by /, e.g. team/app.
+    pub name: String,
+    /// Branch of a multibranch project.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// Include the job in the tray icon state.
+    #[serde(default = "default_show_in_tray")]
+    pub show_in_tray: bool,
+    #[serde(default)]
+    pub notifications: NotificationConfig,
+}
+
+/// The API flavour spoken by a Woodpecker compatible server.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum WoodpeckerApi {
+    /// Woodpecker 2 or newer.
+    #[default]
+    Woodpecker,
+    /// Drone, or Woodpecker before version 2.
+    Drone,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+pub struct WoodpeckerRepoConfig {
+    /// Repository in the form owner/name.
+    pub name: String,
+    /// Branch whose pipelines are monitored.
+    pub main_branch: String,
+    /// Include the repo in the tray icon state.
+    #[serde(default = "default_show_in_tray")]
+    pub show_in_tray: bool,
+    #[serde(default)]
+    pub notifications: NotificationConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+pub struct HttpEndpointConfig {
+    /// Name shown for the endpoint.
+    pub name: String,
+    /// URL returning a JSON document.
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<HttpHeaderConfig>,
+    /// JSON Pointer (/build/state) or simple JSONPath ($.build.state) to the status value.
+    pub status: String,
+    /// Maps status values to states.
+    pub states: BTreeMap<String, MappedState>,
+    /// Page opened when the endpoint is clicked in the tray menu.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+    /// Include the endpoint in the tray icon state.
+    #[serde(default = "default_show_in_tray")]
+    pub show_in_tray: bool,
+    #[serde(default)]
+    pub notifications: NotificationConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+pub struct HttpHeaderConfig {
+    pub name: String,
+    /// Header value, ${NAME} is replaced with the environment variable NAME.
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+pub struct CommandConfig {
+    /// Name shown for the command.
+    pub name: String,
+    /// Shell command to run, e.g. make check.
+    pub command: String,
+    /// Directory to run the command in, ~ is the home directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<PathBuf>,
+    /// Additional environment variables for the command.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Seconds after which the command is killed and reported as failed.
+    #[serde(default = "default_command_timeout_seconds")]
+    pub timeout_seconds: u64,
+    /// Maps the trimmed output to states. If empty, exit code 0 is ok and anything else a failure.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub states: BTreeMap<String, MappedState>,
+    /// Include the command in the tray icon state.
+    #[serde(default = "default_show_in_tray")]
+    pub show_in_tray: bool,
+    #[serde(default)]
+    pub notifications: NotificationConfig,
+}
+
+/// The state that values reported by the http and command providers are mapped to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MappedState {
+    Ok,
+    OkPending,
+    Failure,
+    FailurePending,
+}
+
+impl From<MappedState> for StateSummary {
+    fn from(state: MappedState) -> Self {
+        match state {
+            MappedState::Ok => StateSummary::Ok,
+            MappedState::OkPending => StateSummary::OkPending,
+            MappedState::Failure => StateSummary::Failure,
+            MappedState::FailurePending => StateSummary::FailurePending,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(default)]
+pub struct NotificationConfig {
+    /// Notify when a pipeline fails.
+    pub on_failure: bool,
+    /// Notify when a pipeline succeeds after a failure.
+    pub on_recovery: bool,
+    /// Notify when a new pipeline run starts.
+    pub on_started: bool,
+    /// Notify when a pipeline run finishes, regardless of its result.
+    pub on_finished: bool,
+    /// Suppress failure notifications while a repo stays red.
+    pub only_on_change: bool,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            on_failure: true,
+            on_recovery: true,
+            on_started: false,
+            on_finished: false,
+            only_on_change: true,
+        }
+    }
+}
+
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
 }
 
 fn default_show_in_tray() -> bool {
     false
 }
 
+fn default_command_timeout_seconds() -> u64 {
+    60
+}
+
 pub fn create_default_config() -> Config {
     Config { providers: vec![] }
 }
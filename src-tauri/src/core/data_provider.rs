@@ -3,7 +3,10 @@ use std::sync::Arc;
 use crate::core::StateSummaryGateway;
 
 pub trait DataProvider: Send + Sync {
-    fn refresh(&mut self);
+    /// Wakes the running provider so it polls right away instead of after its interval.
+    fn refresh(&self);
+    /// Polls every repo once on the calling thread and publishes the results to the gateway.
+    fn poll_now(&self, state_summary_gateway: &StateSummaryGateway);
     fn start(&mut self, state_summary_gateway: Arc<StateSummaryGateway>);
     fn stop(&mut self);
 }
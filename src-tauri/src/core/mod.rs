@@ -1,6 +1,16 @@
 pub mod config;
 mod data_provider;
+mod notifications;
+mod poller;
+mod provider_set;
+mod repo_status;
 mod state_summary;
 
 pub use data_provider::DataProvider;
-pub use state_summary::{StateSummary, StateSummaryAdapter, StateSummaryGateway};
+pub use notifications::{NotificationController, Notifier, Transition};
+pub use poller::{Poller, StatusFetcher};
+pub use provider_set::ProviderSet;
+pub use repo_status::{RepoStatus, RepoStatusAdapter};
+pub use state_summary::{
+    combine_summaries, summarize, StateSummary, StateSummaryAdapter, StateSummaryGateway,
+};
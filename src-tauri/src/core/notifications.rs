@@ -0,0 +1,234 @@
+use std::sync::Mutex;
+
+use crate::core::config::NotificationConfig;
+use crate::core::{RepoStatus, RepoStatusAdapter, StateSummary};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    Started,
+    Finished,
+    Failed,
+    Recovered,
+}
+
+pub trait Notifier: Send + Sync {
+    fn notify(&self, title: &str, body: &str);
+}
+
+/// Watches repo status changes and raises notifications according to the rules of each repo.
+pub struct NotificationController {
+    notifier: Box<dyn Notifier>,
+    previous_statuses: Mutex<Vec<RepoStatus>>,
+}
+
+impl NotificationController {
+    pub fn new(notifier: Box<dyn Notifier>) -> Self {
+        Self {
+            notifier,
+            previous_statuses: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl RepoStatusAdapter for NotificationController {
+    fn set_repo_status(&self, status: &RepoStatus) {
+        // A failed fetch says nothing about the pipeline, the last fetched status stays the
+        // baseline for the next transition.
+        if status.state == StateSummary::Error {
+            return;
+        }
+        let Ok(mut previous_statuses) = self.previous_statuses.lock() else {
+            return;
+        };
+        let Some(previous) = previous_statuses
+            .iter_mut()
+            .find(|previous| previous.key() == status.key())
+        else {
+            // The first status of a repo is not a transition, so nobody gets notified on startup.
+            previous_statuses.push(status.clone());
+            return;
+        };
+        for transition in detect_transitions(previous, status) {
+            if should_notify(&status.notifications, transition, previous.state) {
+                let body = notification_body(transition, status.state);
+                match &status.detail {
+                    Some(detail) => self
+                        .notifier
+                        .notify(&status.title(), &format!("{}: {}", body, detail)),
+                    None => self.notifier.notify(&status.title(), body),
+                }
+            }
+        }
+        *previous = status.clone();
+    }
+
+    fn remove_repo_status(&self, status: &RepoStatus) {
+        if let Ok(mut previous_statuses) = self.previous_statuses.lock() {
+            previous_statuses.retain(|previous| previous.key() != status.key());
+        }
+    }
+}
+
+pub fn detect_transitions(previous: &RepoStatus, next: &RepoStatus) -> Vec<Transition> {
+    let new_run = next.run_id.is_some() && next.run_id != previous.run_id;
+    let was_pending = is_pending(previous.state);
+    let is_now_pending = is_pending(next.state);
+    let mut transitions = Vec::new();
+
+    if is_now_pending && (!was_pending || new_run) {
+        transitions.push(Transition::Started);
+    }
+    if !is_now_pending && (was_pending || new_run) {
+        transitions.push(Transition::Finished);
+    }
+    if next.state == StateSummary::Failure && (previous.state != StateSummary::Failure || new_run) {
+        transitions.push(Transition::Failed);
+    }
+    if next.state == StateSummary::Ok && last_result(previous.state) == StateSummary::Failure {
+        transitions.push(Transition::Recovered);
+    }
+    transitions
+}
+
+fn should_notify(
+    rules: &NotificationConfig,
+    transition: Transition,
+    previous: StateSummary,
+) -> bool {
+    match transition {
+        Transition::Started => rules.on_started,
+        Transition::Finished => rules.on_finished,
+        Transition::Failed => {
+            rules.on_failure
+                && !(rules.only_on_change && last_result(previous) == StateSummary::Failure)
+        }
+        Transition::Recovered => rules.on_recovery,
+    }
+}
+
+fn is_pending(state: StateSummary) -> bool {
+    matches!(
+        state,
+        StateSummary::OkPending | StateSummary::FailurePending
+    )
+}
+
+/// The result of the last completed run, ignoring any run that is still in progress.
+fn last_result(state: StateSummary) -> StateSummary {
+    match state {
+        StateSummary::Ok | StateSummary::OkPending => StateSummary::Ok,
+        StateSummary::Failure | StateSummary::FailurePending => StateSummary::Failure,
+        StateSummary::Error => StateSummary::Error,
+    }
+}
+
+fn notification_body(transition: Transition, state: StateSummary) -> &'static str {
+    match transition {
+        Transition::Started => "Pipeline started",
+        Transition::Finished => match state {
+            StateSummary::Failure => "Pipeline finished with failures",
+            _ => "Pipeline finished successfully",
+        },
+        Transition::Failed => "Pipeline failed",
+        Transition::Recovered => "Pipeline is green again",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::{NotificationController, Notifier};
+    use crate::core::config::NotificationConfig;
+    use crate::core::{RepoStatus, RepoStatusAdapter, StateSummary};
+
+    struct TestNotifier {
+        bodies: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Notifier for TestNotifier {
+        fn notify(&self, _title: &str, body: &str) {
+            if let Ok(mut bodies) = self.bodies.lock() {
+                bodies.push(body.to_string());
+            }
+        }
+    }
+
+    fn controller() -> (NotificationController, Arc<Mutex<Vec<String>>>) {
+        let bodies = Arc::new(Mutex::new(Vec::new()));
+        let controller = NotificationController::new(Box::new(TestNotifier {
+            bodies: bodies.clone(),
+        }));
+        (controller, bodies)
+    }
+
+    fn status(state: StateSummary, run_id: u64) -> RepoStatus {
+        let mut status = RepoStatus::new("github", "org/repo", "main", state);
+        status.run_id = Some(run_id);
+        status
+    }
+
+    #[test]
+    fn notifies_on_failure_and_recovery() {
+        let (controller, bodies) = controller();
+
+        controller.set_repo_status(&status(StateSummary::Ok, 1));
+        controller.set_repo_status(&status(StateSummary::Failure, 2));
+        controller.set_repo_status(&status(StateSummary::Failure, 2));
+        controller.set_repo_status(&status(StateSummary::Ok, 3));
+
+        assert_eq!(
+            *bodies.lock().unwrap(),
+            vec!["Pipeline failed", "Pipeline is green again"]
+        );
+    }
+
+    #[test]
+    fn fetch_errors_do_not_count_as_transitions() {
+        let (controller, bodies) = controller();
+
+        controller.set_repo_status(&status(StateSummary::Failure, 1));
+        controller.set_repo_status(&status(StateSummary::Error, 1));
+        controller.set_repo_status(&status(StateSummary::Failure, 1));
+        controller.set_repo_status(&status(StateSummary::Error, 1));
+        controller.set_repo_status(&status(StateSummary::Ok, 2));
+
+        assert_eq!(*bodies.lock().unwrap(), vec!["Pipeline is green again"]);
+    }
+
+    #[test]
+    fn only_on_change_suppresses_repeated_failures() {
+        let (controller, bodies) = controller();
+
+        controller.set_repo_status(&status(StateSummary::Failure, 1));
+        controller.set_repo_status(&status(StateSummary::FailurePending, 2));
+        controller.set_repo_status(&status(StateSummary::Failure, 2));
+
+        assert!(bodies.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn notifies_every_failure_without_only_on_change() {
+        let (controller, bodies) = controller();
+        let rules = NotificationConfig {
+            only_on_change: false,
+            on_started: true,
+            ..NotificationConfig::default()
+        };
+
+        for (state, run_id) in [
+            (StateSummary::Failure, 1),
+            (StateSummary::FailurePending, 2),
+            (StateSummary::Failure, 2),
+        ] {
+            let mut status = status(state, run_id);
+            status.notifications = rules.clone();
+            controller.set_repo_status(&status);
+        }
+
+        assert_eq!(
+            *bodies.lock().unwrap(),
+            vec!["Pipeline started", "Pipeline failed"]
+        );
+    }
+}
@@ -0,0 +1,534 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::core::{DataProvider, RepoStatus, RepoStatusAdapter, StateSummary, StateSummaryGateway};
+
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(60);
+// Failing repos are retried less and less often, but at least this often.
+const MAX_BACKOFF: Duration = Duration::from_secs(30 * 60);
+
+/// The provider specific part of polling: fetching the statuses of a single monitored repo.
+///
+/// A [`Poller`] turns a fetcher into a [`DataProvider`] by owning the polling thread, the
+/// interval and the stop signal.
+pub trait StatusFetcher: Send + Sync + 'static {
+    /// A monitored repo, job, endpoint or command.
+    type Repo;
+    /// Shared by all fetches of a polling thread, usually an HTTP client.
+    type Client;
+
+    /// The provider name used in log messages, e.g. `GitHub`.
+    fn name(&self) -> &'static str;
+    fn repos(&self) -> &[Self::Repo];
+    /// Describes a repo in log messages.
+    fn describe(&self, repo: &Self::Repo) -> String;
+    fn client(&self) -> Self::Client;
+    /// Fetches the current statuses of a repo, which has none if nothing ran yet.
+    fn fetch(&self, client: &Self::Client, repo: &Self::Repo) -> Result<Vec<RepoStatus>, String>;
+    /// Whether the status was published for the repo, so it can be removed on stop.
+    fn reports(&self, repo: &Self::Repo, status: &RepoStatus) -> bool;
+    /// The status shown for a repo that failed before any status was fetched, the poller sets
+    /// the error state and message.
+    fn error_status(&self, repo: &Self::Repo) -> RepoStatus;
+    /// The server statuses are fetched from, if the provider can talk to more than one.
+    fn host(&self) -> Option<&str> {
+        None
+    }
+    /// How long to wait after a poll, providers with a limited request budget may stretch it.
+    fn poll_delay(&self, poll_interval: Duration) -> Duration {
+        poll_interval
+    }
+}
+
+pub struct Poller<F: StatusFetcher> {
+    fetcher: Arc<F>,
+    poll_interval: Duration,
+    stop_signal: Arc<AtomicBool>,
+    wake_signal: Arc<AtomicBool>,
+    thread_handle: Option<JoinHandle<()>>,
+    gateway: Option<Arc<StateSummaryGateway>>,
+    health: Arc<Mutex<Vec<RepoHealth>>>,
+}
+
+/// Consecutive fetch failures of a repo, indexed like [`StatusFetcher::repos`].
+#[derive(Default)]
+struct RepoHealth {
+    failures: u32,
+    retry_at: Option<Instant>,
+    /// Published by the poller itself when no fetched status of the repo was known.
+    error_status: Option<RepoStatus>,
+}
+
+impl<F: StatusFetcher> Poller<F> {
+    pub fn new(fetcher: F) -> Self {
+        let health = fetcher
+            .repos()
+            .iter()
+            .map(|_| RepoHealth::default())
+            .collect();
+        Self {
+            fetcher: Arc::new(fetcher),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            stop_signal: Arc::new(AtomicBool::new(false)),
+            wake_signal: Arc::new(AtomicBool::new(false)),
+            thread_handle: None,
+            gateway: None,
+            health: Arc::new(Mutex::new(health)),
+        }
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Polls every repo right away, including those waiting for their backoff to end.
+    pub fn poll_once(&self, client: &F::Client, gateway: &StateSummaryGateway) {
+        poll_repos(
+            &*self.fetcher,
+            client,
+            gateway,
+            &self.health,
+            self.poll_interval,
+            true,
+        );
+    }
+}
+
+impl<F: StatusFetcher> DataProvider for Poller<F> {
+    fn refresh(&self) {
+        if self.thread_handle.is_some() {
+            self.wake_signal.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn poll_now(&self, state_summary_gateway: &StateSummaryGateway) {
+        self.poll_once(&self.fetcher.client(), state_summary_gateway);
+    }
+
+    fn start(&mut self, state_summary_gateway: Arc<StateSummaryGateway>) {
+        eprintln!("Starting {} provider", self.fetcher.name());
+        if self.thread_handle.is_some() {
+            return;
+        }
+
+        self.stop_signal.store(false, Ordering::Relaxed);
+        self.gateway = Some(state_summary_gateway.clone());
+        let fetcher = self.fetcher.clone();
+        let poll_interval = self.poll_interval;
+        let stop_signal = self.stop_signal.clone();
+        let wake_signal = self.wake_signal.clone();
+        let health = self.health.clone();
+
+        self.thread_handle = Some(thread::spawn(move || {
+            let client = fetcher.client();
+            // An explicit refresh retries failing repos without waiting for their backoff.
+            let mut woken = false;
+
+            loop {
+                if stop_signal.load(Ordering::Relaxed) {
+                    break;
+                }
+
+                poll_repos(
+                    &*fetcher,
+                    &client,
+                    &state_summary_gateway,
+                    &health,
+                    poll_interval,
+                    woken,
+                );
+
+                woken = sleep_with_stop(
+                    &stop_signal,
+                    &wake_signal,
+                    fetcher.poll_delay(poll_interval),
+                );
+            }
+        }));
+    }
+
+    fn stop(&mut self) {
+        self.stop_signal.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+        let error_statuses: Vec<RepoStatus> = match self.health.lock() {
+            Ok(mut health) => health
+                .iter_mut()
+                .filter_map(|repo_health| repo_health.error_status.take())
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        if let Some(gateway) = self.gateway.take() {
+            for status in gateway.repo_statuses() {
+                if self
+                    .fetcher
+                    .repos()
+                    .iter()
+                    .any(|repo| reports(&*self.fetcher, repo, &status))
+                    || error_statuses
+                        .iter()
+                        .any(|error_status| error_status.key() == status.key())
+                {
+                    gateway.remove_repo_status(&status);
+                }
+            }
+        }
+    }
+}
+
+fn poll_repos<F: StatusFetcher>(
+    fetcher: &F,
+    client: &F::Client,
+    gateway: &StateSummaryGateway,
+    health: &Mutex<Vec<RepoHealth>>,
+    poll_interval: Duration,
+    ignore_backoff: bool,
+) {
+    // Decide up front which repos are due, the lock must not be held during the fetches.
+    let now = Instant::now();
+    let due: Vec<usize> = match health.lock() {
+        Ok(health) => health
+            .iter()
+            .enumerate()
+            .filter(|(_, repo_health)| {
+                ignore_backoff || repo_health.retry_at.is_none_or(|retry_at| retry_at <= now)
+            })
+            .map(|(index, _)| index)
+            .collect(),
+        Err(_) => return,
+    };
+    for index in due {
+        let Some(repo) = fetcher.repos().get(index) else {
+            return;
+        };
+        let description = fetcher.describe(repo);
+        eprintln!("{} provider polling {}", fetcher.name(), description);
+        let result = fetcher.fetch(client, repo);
+
+        let Ok(mut health) = health.lock() else {
+            return;
+        };
+        let Some(repo_health) = health.get_mut(index) else {
+            return;
+        };
+        match result {
+            Ok(mut statuses) => {
+                for status in &mut statuses {
+                    status.host = fetcher.host().map(str::to_string);
+                }
+                for status in &statuses {
+                    eprintln!(
+                        "{} provider summary for {} is {:?}",
+                        fetcher.name(),
+                        status.title(),
+                        status.state
+                    );
+                    gateway.set_repo_status(status);
+                }
+                // Fetched statuses replace the error status, unless they have another key.
+                if let Some(error_status) = repo_health.error_status.take().filter(|error_status| {
+                    !statuses
+                        .iter()
+                        .any(|status| status.key() == error_status.key())
+                }) {
+                    gateway.remove_repo_status(&error_status);
+                }
+                *repo_health = RepoHealth::default();
+            }
+            Err(error) => {
+                repo_health.failures += 1;
+                let backoff = backoff(poll_interval, repo_health.failures);
+                repo_health.retry_at = Some(Instant::now() + backoff);
+                eprintln!(
+                    "{} provider error for {} ({} in a row, retrying in {}s): {}",
+                    fetcher.name(),
+                    description,
+                    repo_health.failures,
+                    backoff.as_secs(),
+                    error
+                );
+                publish_error(fetcher, repo, repo_health, gateway, &error);
+            }
+        }
+    }
+}
+
+/// Marks the known statuses of a failing repo as errors, so it does not look green.
+fn publish_error<F: StatusFetcher>(
+    fetcher: &F,
+    repo: &F::Repo,
+    repo_health: &mut RepoHealth,
+    gateway: &StateSummaryGateway,
+    error: &str,
+) {
+    let mut statuses: Vec<RepoStatus> = gateway
+        .repo_statuses()
+        .into_iter()
+        .filter(|status| reports(fetcher, repo, status))
+        .collect();
+    if statuses.is_empty() && repo_health.error_status.is_none() {
+        let mut error_status = fetcher.error_status(repo);
+        error_status.host = fetcher.host().map(str::to_string);
+        repo_health.error_status = Some(error_status);
+    }
+    if let Some(error_status) = repo_health.error_status.clone().filter(|error_status| {
+        !statuses
+            .iter()
+            .any(|status| status.key() == error_status.key())
+    }) {
+        statuses.push(error_status);
+    }
+
+    let detail = if repo_health.failures > 1 {
+        format!("{} (failed {} times in a row)", error, repo_health.failures)
+    } else {
+        error.to_string()
+    };
+    for mut status in statuses {
+        status.state = StateSummary::Error;
+        status.detail = Some(detail.clone());
+        gateway.set_repo_status(&status);
+    }
+}
+
+/// Whether the status was published for the repo by this fetcher, and not another instance.
+fn reports<F: StatusFetcher>(fetcher: &F, repo: &F::Repo, status: &RepoStatus) -> bool {
+    status.host.as_deref() == fetcher.host() && fetcher.reports(repo, status)
+}
+
+/// Doubles the delay with every consecutive failure, starting at the poll interval.
+fn backoff(poll_interval: Duration, failures: u32) -> Duration {
+    let factor = 2u32.saturating_pow(failures.saturating_sub(1));
+    poll_interval
+        .saturating_mul(factor)
+        .min(MAX_BACKOFF)
+        .max(poll_interval)
+}
+
+/// Sleeps for the duration, but returns early once the poller is stopped or woken up.
+///
+/// Returns whether the poller was woken up.
+fn sleep_with_stop(stop_signal: &AtomicBool, wake_signal: &AtomicBool, duration: Duration) -> bool {
+    let mut remaining = duration;
+    let tick = Duration::from_millis(200);
+    while remaining > Duration::ZERO {
+        if stop_signal.load(Ordering::Relaxed) {
+            break;
+        }
+        if wake_signal.swap(false, Ordering::Relaxed) {
+            return true;
+        }
+        let sleep_for = if remaining < tick { remaining } else { tick };
+        thread::sleep(sleep_for);
+        remaining = remaining.saturating_sub(sleep_for);
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    use super::{backoff, poll_repos, Poller, RepoHealth, StatusFetcher};
+    use crate::core::{
+        DataProvider, RepoStatus, RepoStatusAdapter, StateSummary, StateSummaryGateway,
+    };
+
+    struct TestFetcher {
+        repos: Vec<&'static str>,
+        polls: Arc<AtomicUsize>,
+    }
+
+    impl StatusFetcher for TestFetcher {
+        type Repo = &'static str;
+        type Client = ();
+
+        fn name(&self) -> &'static str {
+            "Test"
+        }
+
+        fn repos(&self) -> &[&'static str] {
+            &self.repos
+        }
+
+        fn describe(&self, repo: &&'static str) -> String {
+            repo.to_string()
+        }
+
+        fn client(&self) {}
+
+        fn fetch(&self, _client: &(), repo: &&'static str) -> Result<Vec<RepoStatus>, String> {
+            self.polls.fetch_add(1, Ordering::Relaxed);
+            match *repo {
+                "broken" => Err("unreachable".to_string()),
+                repo => Ok(vec![RepoStatus::new(
+                    "test",
+                    repo,
+                    "main",
+                    StateSummary::Ok,
+                )]),
+            }
+        }
+
+        fn reports(&self, repo: &&'static str, status: &RepoStatus) -> bool {
+            status.provider == "test" && status.repo == *repo
+        }
+
+        fn error_status(&self, repo: &&'static str) -> RepoStatus {
+            RepoStatus::new("test", *repo, "main", StateSummary::Error)
+        }
+    }
+
+    #[test]
+    fn publishes_statuses_and_removes_them_on_stop() {
+        let mut poller = Poller::new(TestFetcher {
+            repos: vec!["app", "broken"],
+            polls: Arc::new(AtomicUsize::new(0)),
+        });
+        let gateway = Arc::new(StateSummaryGateway::new());
+        gateway.set_repo_status(&RepoStatus::new("other", "lib", "main", StateSummary::Ok));
+
+        poller.start(gateway.clone());
+        while gateway.repo_statuses().len() < 2 {
+            thread::sleep(Duration::from_millis(10));
+        }
+        poller.stop();
+
+        let statuses = gateway.repo_statuses();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].provider, "other");
+    }
+
+    #[test]
+    fn failing_repo_is_shown_as_error_and_backs_off() {
+        let polls = Arc::new(AtomicUsize::new(0));
+        let fetcher = TestFetcher {
+            repos: vec!["broken"],
+            polls: polls.clone(),
+        };
+        let gateway = StateSummaryGateway::new();
+        let health = Mutex::new(vec![RepoHealth::default()]);
+        let interval = Duration::from_secs(60);
+
+        poll_repos(&fetcher, &(), &gateway, &health, interval, false);
+        poll_repos(&fetcher, &(), &gateway, &health, interval, false);
+        assert_eq!(polls.load(Ordering::Relaxed), 1);
+
+        poll_repos(&fetcher, &(), &gateway, &health, interval, true);
+        assert_eq!(polls.load(Ordering::Relaxed), 2);
+
+        let statuses = gateway.repo_statuses();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].state, StateSummary::Error);
+        assert_eq!(
+            statuses[0].detail.as_deref(),
+            Some("unreachable (failed 2 times in a row)")
+        );
+    }
+
+    #[test]
+    fn health_is_not_locked_while_fetching() {
+        struct LockCheckingFetcher {
+            health: Arc<Mutex<Vec<RepoHealth>>>,
+        }
+
+        impl StatusFetcher for LockCheckingFetcher {
+            type Repo = &'static str;
+            type Client = ();
+
+            fn name(&self) -> &'static str {
+                "Test"
+            }
+
+            fn repos(&self) -> &[&'static str] {
+                &["app"]
+            }
+
+            fn describe(&self, repo: &&'static str) -> String {
+                repo.to_string()
+            }
+
+            fn client(&self) {}
+
+            fn fetch(&self, _client: &(), repo: &&'static str) -> Result<Vec<RepoStatus>, String> {
+                assert!(self.health.try_lock().is_ok());
+                Ok(vec![RepoStatus::new(
+                    "test",
+                    *repo,
+                    "main",
+                    StateSummary::Ok,
+                )])
+            }
+
+            fn reports(&self, repo: &&'static str, status: &RepoStatus) -> bool {
+                status.provider == "test" && status.repo == *repo
+            }
+
+            fn error_status(&self, repo: &&'static str) -> RepoStatus {
+                RepoStatus::new("test", *repo, "main", StateSummary::Error)
+            }
+        }
+
+        let health = Arc::new(Mutex::new(vec![RepoHealth::default()]));
+        let fetcher = LockCheckingFetcher {
+            health: health.clone(),
+        };
+        let gateway = StateSummaryGateway::new();
+
+        poll_repos(
+            &fetcher,
+            &(),
+            &gateway,
+            &health,
+            Duration::from_secs(60),
+            false,
+        );
+
+        let statuses = gateway.repo_statuses();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].state, StateSummary::Ok);
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum() {
+        let minute = Duration::from_secs(60);
+
+        assert_eq!(backoff(minute, 1), minute);
+        assert_eq!(backoff(minute, 3), minute * 4);
+        assert_eq!(backoff(minute, 40), Duration::from_secs(30 * 60));
+        assert_eq!(
+            backoff(Duration::from_secs(3600), 2),
+            Duration::from_secs(3600)
+        );
+    }
+
+    #[test]
+    fn refresh_polls_before_the_interval_ends() {
+        let polls = Arc::new(AtomicUsize::new(0));
+        let mut poller = Poller::new(TestFetcher {
+            repos: vec!["app"],
+            polls: polls.clone(),
+        })
+        .with_poll_interval(Duration::from_secs(3600));
+        let gateway = Arc::new(StateSummaryGateway::new());
+
+        poller.start(gateway);
+        while polls.load(Ordering::Relaxed) < 1 {
+            thread::sleep(Duration::from_millis(10));
+        }
+        poller.refresh();
+        while polls.load(Ordering::Relaxed) < 2 {
+            thread::sleep(Duration::from_millis(10));
+        }
+        poller.stop();
+    }
+}
@@ -0,0 +1,159 @@
+use std::sync::Arc;
+
+use crate::core::config::ProviderConfig;
+use crate::core::{DataProvider, StateSummaryGateway};
+
+struct RunningProvider {
+    config: ProviderConfig,
+    provider: Box<dyn DataProvider>,
+}
+
+/// The providers that are currently running, together with the config they were created from.
+pub struct ProviderSet {
+    running: Vec<RunningProvider>,
+}
+
+impl ProviderSet {
+    pub fn new() -> Self {
+        Self {
+            running: Vec::new(),
+        }
+    }
+
+    /// Takes out the providers whose config was removed or changed, providers with an unchanged
+    /// config keep running.
+    ///
+    /// The caller stops the returned providers before starting new ones with [`Self::start_missing`].
+    /// Stopping waits for a poll in flight, so it should not happen while the set is locked.
+    pub fn remove_stale(&mut self, configs: &[ProviderConfig]) -> Vec<Box<dyn DataProvider>> {
+        let (kept, removed): (Vec<_>, Vec<_>) = self
+            .running
+            .drain(..)
+            .partition(|running| configs.contains(&running.config));
+        self.running = kept;
+        removed
+            .into_iter()
+            .map(|running| running.provider)
+            .collect()
+    }
+
+    /// Starts providers for the configs that have no running provider yet.
+    pub fn start_missing(
+        &mut self,
+        configs: &[ProviderConfig],
+        gateway: &Arc<StateSummaryGateway>,
+        create_provider: impl Fn(&ProviderConfig) -> Box<dyn DataProvider>,
+    ) {
+        for config in configs {
+            if self.running.iter().any(|running| &running.config == config) {
+                continue;
+            }
+            let mut provider = create_provider(config);
+            provider.start(gateway.clone());
+            self.running.push(RunningProvider {
+                config: config.clone(),
+                provider,
+            });
+        }
+    }
+
+    /// Makes every running provider poll right away.
+    pub fn refresh(&self) {
+        for running in &self.running {
+            running.provider.refresh();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.running.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.running.is_empty()
+    }
+}
+
+impl Default for ProviderSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::ProviderSet;
+    use crate::core::config::{ProviderConfig, TokenConfig};
+    use crate::core::{DataProvider, StateSummaryGateway};
+
+    struct TestProvider {
+        name: String,
+        events: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl DataProvider for TestProvider {
+        fn refresh(&self) {}
+
+        fn poll_now(&self, _state_summary_gateway: &StateSummaryGateway) {}
+
+        fn start(&mut self, _state_summary_gateway: Arc<StateSummaryGateway>) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("start {}", self.name));
+        }
+
+        fn stop(&mut self) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("stop {}", self.name));
+        }
+    }
+
+    fn gitlab_config(token_env: &str) -> ProviderConfig {
+        ProviderConfig::Gitlab {
+            url: None,
+            token: TokenConfig {
+                env: token_env.to_string(),
+            },
+            ca_cert: None,
+            insecure_skip_verify: false,
+            repos: vec![],
+        }
+    }
+
+    #[test]
+    fn restarts_only_changed_providers() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let create_provider = |config: &ProviderConfig| -> Box<dyn DataProvider> {
+            let ProviderConfig::Gitlab { token, .. } = config else {
+                unreachable!()
+            };
+            Box::new(TestProvider {
+                name: token.env.clone(),
+                events: events.clone(),
+            })
+        };
+        let gateway = Arc::new(StateSummaryGateway::new());
+        let mut providers = ProviderSet::new();
+
+        providers.start_missing(
+            &[gitlab_config("A"), gitlab_config("B")],
+            &gateway,
+            create_provider,
+        );
+        let configs = [gitlab_config("A"), gitlab_config("C")];
+        for mut provider in providers.remove_stale(&configs) {
+            provider.stop();
+        }
+        providers.start_missing(&configs, &gateway, create_provider);
+
+        assert_eq!(providers.len(), 2);
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec!["start A", "start B", "stop B", "start C"]
+        );
+    }
+}
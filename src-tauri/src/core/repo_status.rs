@@ -0,0 +1,80 @@
+use serde::Serialize;
+
+use crate::core::config::NotificationConfig;
+use crate::core::StateSummary;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RepoStatus {
+    pub provider: String,
+    /// The server of the provider, tells the same repo on two hosts apart.
+    pub host: Option<String>,
+    pub repo: String,
+    pub branch: String,
+    pub workflow: Option<String>,
+    pub state: StateSummary,
+    pub show_in_tray: bool,
+    #[serde(skip)]
+    pub notifications: NotificationConfig,
+    pub run_id: Option<u64>,
+    pub url: Option<String>,
+    /// What caused the state, e.g. the name of the failed job.
+    pub detail: Option<String>,
+    pub started_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+impl RepoStatus {
+    pub fn new(
+        provider: impl Into<String>,
+        repo: impl Into<String>,
+        branch: impl Into<String>,
+        state: StateSummary,
+    ) -> Self {
+        Self {
+            provider: provider.into(),
+            host: None,
+            repo: repo.into(),
+            branch: branch.into(),
+            workflow: None,
+            state,
+            show_in_tray: true,
+            notifications: NotificationConfig::default(),
+            run_id: None,
+            url: None,
+            detail: None,
+            started_at: None,
+            updated_at: None,
+        }
+    }
+
+    /// Two statuses with the same key describe the same monitored pipeline.
+    pub fn key(&self) -> (&str, Option<&str>, &str, &str, Option<&str>) {
+        (
+            &self.provider,
+            self.host.as_deref(),
+            &self.repo,
+            &self.branch,
+            self.workflow.as_deref(),
+        )
+    }
+
+    /// A human readable name like `org/repo (main, ci.yml)`, leaving out what is not set.
+    pub fn title(&self) -> String {
+        let details: Vec<&str> = [Some(self.branch.as_str()), self.workflow.as_deref()]
+            .into_iter()
+            .flatten()
+            .filter(|detail| !detail.is_empty())
+            .collect();
+        if details.is_empty() {
+            self.repo.clone()
+        } else {
+            format!("{} ({})", self.repo, details.join(", "))
+        }
+    }
+}
+
+pub trait RepoStatusAdapter: Send + Sync {
+    fn set_repo_status(&self, status: &RepoStatus);
+    /// Called when a repo is no longer monitored, e.g. after it was removed from the config.
+    fn remove_repo_status(&self, status: &RepoStatus);
+}
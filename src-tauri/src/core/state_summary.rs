@@ -1,11 +1,17 @@
 use std::sync::RwLock;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use serde::Serialize;
+
+use crate::core::{RepoStatus, RepoStatusAdapter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum StateSummary {
     Ok,
     OkPending,
     Failure,
     FailurePending,
+    /// The status could not be fetched, e.g. because the token was revoked.
+    Error,
 }
 
 pub trait StateSummaryAdapter: Send + Sync {
@@ -14,12 +20,16 @@ pub trait StateSummaryAdapter: Send + Sync {
 
 pub struct StateSummaryGateway {
     controllers: RwLock<Vec<Box<dyn StateSummaryAdapter>>>,
+    repo_controllers: RwLock<Vec<Box<dyn RepoStatusAdapter>>>,
+    repo_statuses: RwLock<Vec<RepoStatus>>,
 }
 
 impl StateSummaryGateway {
     pub fn new() -> Self {
         Self {
             controllers: RwLock::new(Vec::new()),
+            repo_controllers: RwLock::new(Vec::new()),
+            repo_statuses: RwLock::new(Vec::new()),
         }
     }
 
@@ -28,6 +38,44 @@ impl StateSummaryGateway {
             controllers.push(controller);
         }
     }
+
+    pub fn add_repo_controller(&self, controller: Box<dyn RepoStatusAdapter>) {
+        if let Ok(mut controllers) = self.repo_controllers.write() {
+            controllers.push(controller);
+        }
+    }
+
+    pub fn repo_statuses(&self) -> Vec<RepoStatus> {
+        match self.repo_statuses.read() {
+            Ok(statuses) => statuses.clone(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn update_repo_status(&self, status: &RepoStatus) -> Option<StateSummary> {
+        let mut statuses = self.repo_statuses.write().ok()?;
+        match statuses
+            .iter_mut()
+            .find(|existing| existing.key() == status.key())
+        {
+            Some(existing) => *existing = status.clone(),
+            None => statuses.push(status.clone()),
+        }
+        summarize(&statuses)
+    }
+
+    fn delete_repo_status(&self, status: &RepoStatus) -> Option<StateSummary> {
+        let mut statuses = self.repo_statuses.write().ok()?;
+        statuses.retain(|existing| existing.key() != status.key());
+        // Without repos left in the tray, it goes back to the neutral icon it starts with.
+        Some(summarize(&statuses).unwrap_or(StateSummary::Ok))
+    }
+}
+
+impl Default for StateSummaryGateway {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl StateSummaryAdapter for StateSummaryGateway {
@@ -39,3 +87,207 @@ impl StateSummaryAdapter for StateSummaryGateway {
         }
     }
 }
+
+impl RepoStatusAdapter for StateSummaryGateway {
+    fn set_repo_status(&self, status: &RepoStatus) {
+        let summary = self.update_repo_status(status);
+        if let Ok(controllers) = self.repo_controllers.read() {
+            for controller in controllers.iter() {
+                controller.set_repo_status(status);
+            }
+        }
+        if let Some(summary) = summary {
+            self.set_state_summary(summary);
+        }
+    }
+
+    fn remove_repo_status(&self, status: &RepoStatus) {
+        let summary = self.delete_repo_status(status);
+        if let Ok(controllers) = self.repo_controllers.read() {
+            for controller in controllers.iter() {
+                controller.remove_repo_status(status);
+            }
+        }
+        if let Some(summary) = summary {
+            self.set_state_summary(summary);
+        }
+    }
+}
+
+/// Combines the states of all repos that are configured to be shown in the tray.
+pub fn summarize(statuses: &[RepoStatus]) -> Option<StateSummary> {
+    statuses
+        .iter()
+        .filter(|status| status.show_in_tray)
+        .fold(None, |combined, status| {
+            Some(combine_summaries(combined, status.state))
+        })
+}
+
+pub fn combine_summaries(current: Option<StateSummary>, next: StateSummary) -> StateSummary {
+    match current {
+        None => next,
+        Some(existing) => {
+            if summary_rank(next) > summary_rank(existing) {
+                next
+            } else {
+                existing
+            }
+        }
+    }
+}
+
+/// Orders the states by how much attention they need in the combined summary.
+///
+/// An error only means the state of a repo is unknown, so it ranks below the known failures of
+/// other repos: one unreachable server must not hide a broken build behind the grey icon.
+fn summary_rank(summary: StateSummary) -> u8 {
+    match summary {
+        StateSummary::Ok => 0,
+        StateSummary::OkPending => 1,
+        StateSummary::Error => 2,
+        StateSummary::FailurePending => 3,
+        StateSummary::Failure => 4,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::{combine_summaries, StateSummary, StateSummaryAdapter, StateSummaryGateway};
+    use crate::core::{RepoStatus, RepoStatusAdapter};
+
+    struct TestSummaryAdapter {
+        latest: Arc<Mutex<Option<StateSummary>>>,
+    }
+
+    impl StateSummaryAdapter for TestSummaryAdapter {
+        fn set_state_summary(&self, state: StateSummary) {
+            if let Ok(mut latest) = self.latest.lock() {
+                *latest = Some(state);
+            }
+        }
+    }
+
+    #[test]
+    fn combined_summary_prefers_failures_over_errors() {
+        let ranked = [
+            StateSummary::Ok,
+            StateSummary::OkPending,
+            StateSummary::Error,
+            StateSummary::FailurePending,
+            StateSummary::Failure,
+        ];
+
+        for (index, &lower) in ranked.iter().enumerate() {
+            for &higher in &ranked[index..] {
+                assert_eq!(combine_summaries(Some(lower), higher), higher);
+                assert_eq!(combine_summaries(Some(higher), lower), higher);
+            }
+        }
+    }
+
+    #[test]
+    fn summary_is_derived_from_all_repos() {
+        let gateway = StateSummaryGateway::new();
+        let latest = Arc::new(Mutex::new(None));
+        gateway.add_controller(Box::new(TestSummaryAdapter {
+            latest: latest.clone(),
+        }));
+
+        gateway.set_repo_status(&RepoStatus::new(
+            "github",
+            "org/a",
+            "main",
+            StateSummary::Failure,
+        ));
+        gateway.set_repo_status(&RepoStatus::new(
+            "gitlab",
+            "org/b",
+            "main",
+            StateSummary::Ok,
+        ));
+
+        assert_eq!(*latest.lock().unwrap(), Some(StateSummary::Failure));
+    }
+
+    #[test]
+    fn newer_status_replaces_older_status_of_same_repo() {
+        let gateway = StateSummaryGateway::new();
+        let latest = Arc::new(Mutex::new(None));
+        gateway.add_controller(Box::new(TestSummaryAdapter {
+            latest: latest.clone(),
+        }));
+
+        gateway.set_repo_status(&RepoStatus::new(
+            "github",
+            "org/a",
+            "main",
+            StateSummary::Failure,
+        ));
+        gateway.set_repo_status(&RepoStatus::new(
+            "github",
+            "org/a",
+            "main",
+            StateSummary::OkPending,
+        ));
+
+        assert_eq!(gateway.repo_statuses().len(), 1);
+        assert_eq!(*latest.lock().unwrap(), Some(StateSummary::OkPending));
+    }
+
+    #[test]
+    fn removing_the_last_tray_repo_resets_summary() {
+        let gateway = StateSummaryGateway::new();
+        let latest = Arc::new(Mutex::new(None));
+        gateway.add_controller(Box::new(TestSummaryAdapter {
+            latest: latest.clone(),
+        }));
+
+        let failing = RepoStatus::new("github", "org/a", "main", StateSummary::Failure);
+        let mut hidden = RepoStatus::new("github", "org/hidden", "main", StateSummary::Failure);
+        hidden.show_in_tray = false;
+        gateway.set_repo_status(&failing);
+        gateway.set_repo_status(&hidden);
+        gateway.remove_repo_status(&failing);
+
+        assert_eq!(*latest.lock().unwrap(), Some(StateSummary::Ok));
+    }
+
+    #[test]
+    fn same_repo_on_two_hosts_is_kept_apart() {
+        let gateway = StateSummaryGateway::new();
+
+        let mut cloud = RepoStatus::new("gitlab", "org/a", "main", StateSummary::Ok);
+        cloud.host = Some("https://gitlab.com/api/v4".to_string());
+        let mut self_hosted = RepoStatus::new("gitlab", "org/a", "main", StateSummary::Failure);
+        self_hosted.host = Some("https://gitlab.example.com/api/v4".to_string());
+        gateway.set_repo_status(&cloud);
+        gateway.set_repo_status(&self_hosted);
+
+        assert_eq!(gateway.repo_statuses().len(), 2);
+    }
+
+    #[test]
+    fn repos_hidden_from_tray_do_not_affect_summary() {
+        let gateway = StateSummaryGateway::new();
+        let latest = Arc::new(Mutex::new(None));
+        gateway.add_controller(Box::new(TestSummaryAdapter {
+            latest: latest.clone(),
+        }));
+
+        let mut noisy = RepoStatus::new("github", "org/noisy", "main", StateSummary::Failure);
+        noisy.show_in_tray = false;
+        gateway.set_repo_status(&noisy);
+        gateway.set_repo_status(&RepoStatus::new(
+            "github",
+            "org/a",
+            "main",
+            StateSummary::Ok,
+        ));
+
+        assert_eq!(gateway.repo_statuses().len(), 2);
+        assert_eq!(*latest.lock().unwrap(), Some(StateSummary::Ok));
+    }
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use tauri::{AppHandle, Emitter, State};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::config_file::ConfigErrorReport;
+use crate::core::config::Config;
+use crate::core::{
+    summarize, RepoStatus, RepoStatusAdapter, StateSummary, StateSummaryAdapter,
+    StateSummaryGateway,
+};
+use crate::data_providers::{rate_limits, RateLimit};
+use crate::setup::AppState;
+
+const REPO_STATUS_EVENT: &str = "repo-status";
+const REPO_STATUS_REMOVED_EVENT: &str = "repo-status-removed";
+const STATE_SUMMARY_EVENT: &str = "state-summary";
+
+struct DashboardController {
+    handle: AppHandle,
+}
+
+impl DashboardController {
+    fn new(handle: AppHandle) -> Self {
+        Self { handle }
+    }
+}
+
+impl StateSummaryAdapter for DashboardController {
+    fn set_state_summary(&self, state: StateSummary) {
+        if let Err(error) = self.handle.emit(STATE_SUMMARY_EVENT, state) {
+            eprintln!("Failed to emit state summary: {}", error);
+        }
+    }
+}
+
+impl RepoStatusAdapter for DashboardController {
+    fn set_repo_status(&self, status: &RepoStatus) {
+        if let Err(error) = self.handle.emit(REPO_STATUS_EVENT, status) {
+            eprintln!("Failed to emit repo status: {}", error);
+        }
+    }
+
+    fn remove_repo_status(&self, status: &RepoStatus) {
+        if let Err(error) = self.handle.emit(REPO_STATUS_REMOVED_EVENT, status) {
+            eprintln!("Failed to emit removed repo status: {}", error);
+        }
+    }
+}
+
+pub fn setup_with(app: &AppHandle, dispatcher: &Arc<StateSummaryGateway>) {
+    dispatcher.add_controller(Box::new(DashboardController::new(app.clone())));
+    dispatcher.add_repo_controller(Box::new(DashboardController::new(app.clone())));
+}
+
+#[tauri::command]
+pub fn get_statuses(state: State<'_, AppState>) -> Vec<RepoStatus> {
+    state.gateway.repo_statuses()
+}
+
+#[tauri::command]
+pub fn get_summary(state: State<'_, AppState>) -> Option<StateSummary> {
+    summarize(&state.gateway.repo_statuses())
+}
+
+#[tauri::command]
+pub fn get_rate_limits() -> Vec<RateLimit> {
+    rate_limits()
+}
+
+#[tauri::command]
+pub fn get_config(state: State<'_, AppState>) -> Result<Config, String> {
+    state
+        .config
+        .lock()
+        .map(|config| config.clone())
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+pub fn get_config_error(state: State<'_, AppState>) -> Option<ConfigErrorReport> {
+    state
+        .config_error
+        .lock()
+        .ok()
+        .and_then(|config_error| config_error.clone())
+}
+
+#[tauri::command]
+pub fn open_config_file(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    app.opener()
+        .open_path(state.config_file.to_string_lossy(), None::<&str>)
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+pub fn refresh_now(state: State<'_, AppState>) {
+    state.refresh_providers();
+}
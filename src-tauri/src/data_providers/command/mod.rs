@@ -0,0 +1,330 @@
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{SecondsFormat, Utc};
+
+use crate::core::config::MappedState;
+use crate::core::{RepoStatus, StateSummary, StatusFetcher};
+use crate::data_providers::RepoOptions;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+/// How long to wait for output after the command exited, a backgrounded child may hold stdout.
+const OUTPUT_GRACE: Duration = Duration::from_millis(200);
+
+#[derive(Clone)]
+pub struct LocalCommand {
+    name: String,
+    command: String,
+    working_dir: Option<PathBuf>,
+    env: BTreeMap<String, String>,
+    timeout: Duration,
+    /// Maps the trimmed stdout to a state. The exit code decides if this is empty.
+    states: BTreeMap<String, MappedState>,
+    options: RepoOptions,
+}
+
+impl LocalCommand {
+    pub fn new(name: String, command: String) -> Self {
+        Self {
+            name,
+            command,
+            working_dir: None,
+            env: BTreeMap::new(),
+            timeout: DEFAULT_TIMEOUT,
+            states: BTreeMap::new(),
+            options: RepoOptions::default(),
+        }
+    }
+
+    pub fn with_working_dir(mut self, working_dir: Option<PathBuf>) -> Self {
+        self.working_dir = working_dir;
+        self
+    }
+
+    pub fn with_env(mut self, env: BTreeMap<String, String>) -> Self {
+        self.env = env;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_states(mut self, states: BTreeMap<String, MappedState>) -> Self {
+        self.states = states;
+        self
+    }
+
+    pub fn with_options(mut self, options: RepoOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    fn reports(&self, status: &RepoStatus) -> bool {
+        status.provider == "command" && status.repo == self.name
+    }
+}
+
+pub struct CommandProvider {
+    commands: Vec<LocalCommand>,
+}
+
+impl CommandProvider {
+    pub fn new(commands: Vec<LocalCommand>) -> Self {
+        Self { commands }
+    }
+}
+
+impl StatusFetcher for CommandProvider {
+    type Repo = LocalCommand;
+    type Client = ();
+
+    fn name(&self) -> &'static str {
+        "Command"
+    }
+
+    fn repos(&self) -> &[LocalCommand] {
+        &self.commands
+    }
+
+    fn describe(&self, command: &LocalCommand) -> String {
+        command.name.clone()
+    }
+
+    fn client(&self) {}
+
+    fn fetch(&self, _client: &(), command: &LocalCommand) -> Result<Vec<RepoStatus>, String> {
+        let started_at = timestamp_now();
+        let state = run_command(command)?;
+        let mut status = RepoStatus::new("command", &command.name, "", state);
+        command.options.apply(&mut status);
+        status.started_at = Some(started_at);
+        status.updated_at = Some(timestamp_now());
+        Ok(vec![status])
+    }
+
+    fn reports(&self, command: &LocalCommand, status: &RepoStatus) -> bool {
+        command.reports(status)
+    }
+
+    fn error_status(&self, command: &LocalCommand) -> RepoStatus {
+        let mut status = RepoStatus::new("command", &command.name, "", StateSummary::Error);
+        command.options.apply(&mut status);
+        status
+    }
+}
+
+fn run_command(command: &LocalCommand) -> Result<StateSummary, String> {
+    let mut process = shell_command(&command.command);
+    process
+        .envs(&command.env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    if let Some(working_dir) = &command.working_dir {
+        process.current_dir(expand_home(working_dir));
+    }
+    let mut child = process.spawn().map_err(|error| error.to_string())?;
+
+    // Read stdout on a separate thread, a full pipe would block the command forever. Chunks are
+    // sent as they arrive, because a backgrounded child can keep the pipe open after the exit.
+    let (sender, receiver) = mpsc::channel();
+    if let Some(mut stdout) = child.stdout.take() {
+        thread::spawn(move || {
+            let mut buffer = [0; 4096];
+            while let Ok(read @ 1..) = stdout.read(&mut buffer) {
+                if sender.send(buffer[..read].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    let deadline = Instant::now() + command.timeout;
+    let exit_status = loop {
+        if let Some(exit_status) = child.try_wait().map_err(|error| error.to_string())? {
+            break exit_status;
+        }
+        if Instant::now() >= deadline {
+            kill_process_group(&mut child);
+            let _ = child.wait();
+            eprintln!(
+                "Command provider timed out after {:?} running {}",
+                command.timeout, command.name
+            );
+            return Ok(StateSummary::Failure);
+        }
+        thread::sleep(Duration::from_millis(100));
+    };
+    let mut output = Vec::new();
+    while let Ok(chunk) = receiver.recv_timeout(OUTPUT_GRACE) {
+        output.extend(chunk);
+        if Instant::now() >= deadline {
+            break;
+        }
+    }
+    let output = String::from_utf8_lossy(&output);
+
+    if command.states.is_empty() {
+        return Ok(if exit_status.success() {
+            StateSummary::Ok
+        } else {
+            StateSummary::Failure
+        });
+    }
+    let output = output.trim();
+    command
+        .states
+        .get(output)
+        .map(|state| StateSummary::from(*state))
+        .ok_or_else(|| format!("Output \"{}\" has no mapping in states", output))
+}
+
+#[cfg(unix)]
+fn shell_command(command: &str) -> Command {
+    use std::os::unix::process::CommandExt;
+
+    let mut process = Command::new("sh");
+    // A process group of its own lets a timeout also kill the children the shell started.
+    process.arg("-c").arg(command).process_group(0);
+    process
+}
+
+#[cfg(windows)]
+fn shell_command(command: &str) -> Command {
+    let mut process = Command::new("cmd");
+    process.arg("/C").arg(command);
+    process
+}
+
+#[cfg(unix)]
+fn kill_process_group(child: &mut Child) {
+    // The group id equals the pid of the shell, see shell_command.
+    let process_group = child.id() as libc::pid_t;
+    unsafe {
+        libc::kill(-process_group, libc::SIGKILL);
+    }
+}
+
+#[cfg(windows)]
+fn kill_process_group(child: &mut Child) {
+    use std::os::windows::process::CommandExt;
+
+    // There are no process groups, taskkill /T kills the tree of processes started by cmd.
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    let killed = Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &child.id().to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .creation_flags(CREATE_NO_WINDOW)
+        .status()
+        .is_ok_and(|status| status.success());
+    if !killed {
+        let _ = child.kill();
+    }
+}
+
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn timestamp_now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::time::{Duration, Instant};
+
+    use super::{run_command, LocalCommand};
+    use crate::core::config::MappedState;
+    use crate::core::StateSummary;
+
+    #[test]
+    fn maps_exit_code_to_state() {
+        let passing = LocalCommand::new("pass".to_string(), "exit 0".to_string());
+        let failing = LocalCommand::new("fail".to_string(), "exit 3".to_string());
+
+        assert_eq!(run_command(&passing), Ok(StateSummary::Ok));
+        assert_eq!(run_command(&failing), Ok(StateSummary::Failure));
+    }
+
+    #[test]
+    fn maps_stdout_with_env_and_working_dir() {
+        let command = LocalCommand::new(
+            "health".to_string(),
+            "echo \"$STATE-$(basename \"$PWD\")\"".to_string(),
+        )
+        .with_working_dir(Some(std::env::temp_dir()))
+        .with_env(BTreeMap::from([("STATE".to_string(), "busy".to_string())]))
+        .with_states(BTreeMap::from([(
+            format!(
+                "busy-{}",
+                std::env::temp_dir().file_name().unwrap().to_string_lossy()
+            ),
+            MappedState::OkPending,
+        )]));
+
+        assert_eq!(run_command(&command), Ok(StateSummary::OkPending));
+    }
+
+    #[test]
+    fn reports_failure_on_timeout() {
+        let command = LocalCommand::new("slow".to_string(), "sleep 5".to_string())
+            .with_timeout(Duration::from_millis(200));
+
+        assert_eq!(run_command(&command), Ok(StateSummary::Failure));
+    }
+
+    #[test]
+    fn does_not_wait_for_backgrounded_children() {
+        let command = LocalCommand::new("daemon".to_string(), "sleep 5 & echo ok".to_string())
+            .with_states(BTreeMap::from([("ok".to_string(), MappedState::Ok)]));
+        let started = Instant::now();
+
+        assert_eq!(run_command(&command), Ok(StateSummary::Ok));
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn timeout_kills_backgrounded_children() {
+        let pid_file = std::env::temp_dir().join(format!("pmon-command-{}", std::process::id()));
+        let command = LocalCommand::new(
+            "slow".to_string(),
+            format!("sleep 5 & echo $! > {}; sleep 5", pid_file.display()),
+        )
+        .with_timeout(Duration::from_millis(500));
+        let started = Instant::now();
+
+        assert_eq!(run_command(&command), Ok(StateSummary::Failure));
+        assert!(started.elapsed() < Duration::from_secs(2));
+        let pid: libc::pid_t = std::fs::read_to_string(&pid_file)
+            .unwrap()
+            .trim()
+            .parse()
+            .unwrap();
+        let _ = std::fs::remove_file(&pid_file);
+        // The killed child is not reaped by the test process, so ask the kernel for its state.
+        let killed_by = Instant::now() + Duration::from_secs(1);
+        while is_running(pid) && Instant::now() < killed_by {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(!is_running(pid));
+    }
+
+    fn is_running(pid: libc::pid_t) -> bool {
+        std::fs::read_to_string(format!("/proc/{}/stat", pid))
+            .map(|stat| !stat.contains(") Z"))
+            .unwrap_or(false)
+    }
+}
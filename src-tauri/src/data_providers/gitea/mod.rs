@@ -0,0 +1,306 @@
+use serde::Deserialize;
+
+use crate::core::{RepoStatus, StateSummary, StatusFetcher};
+use crate::data_providers::RepoOptions;
+
+// Runs of all workflows are listed together, fetch enough to find the latest two of one workflow.
+const RUNS_PER_PAGE: usize = 20;
+
+#[derive(Clone)]
+pub struct GiteaRepo {
+    name: String,
+    main_branch: String,
+    workflow: Option<String>,
+    options: RepoOptions,
+}
+
+impl GiteaRepo {
+    pub fn new(name: String, main_branch: String) -> Self {
+        Self {
+            name,
+            main_branch,
+            workflow: None,
+            options: RepoOptions::default(),
+        }
+    }
+
+    pub fn with_workflow(mut self, workflow: Option<String>) -> Self {
+        self.workflow = workflow;
+        self
+    }
+
+    pub fn with_options(mut self, options: RepoOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    fn reports(&self, status: &RepoStatus) -> bool {
+        status.provider == "gitea"
+            && status.repo == self.name
+            && status.branch == self.main_branch
+            && status.workflow == self.workflow
+    }
+}
+
+pub struct GiteaProvider {
+    token_env: String,
+    repos: Vec<GiteaRepo>,
+    api_base_url: String,
+}
+
+impl GiteaProvider {
+    /// `url` is the root of the Gitea or Forgejo instance, e.g. `https://codeberg.org`.
+    pub fn new(url: &str, token_env: String, repos: Vec<GiteaRepo>) -> Self {
+        Self {
+            token_env,
+            repos,
+            api_base_url: format!("{}/api/v1", url.trim_end_matches('/')),
+        }
+    }
+}
+
+impl StatusFetcher for GiteaProvider {
+    type Repo = GiteaRepo;
+    type Client = reqwest::blocking::Client;
+
+    fn name(&self) -> &'static str {
+        "Gitea"
+    }
+
+    fn repos(&self) -> &[GiteaRepo] {
+        &self.repos
+    }
+
+    fn describe(&self, repo: &GiteaRepo) -> String {
+        format!("{} on {}", repo.name, repo.main_branch)
+    }
+
+    fn client(&self) -> reqwest::blocking::Client {
+        reqwest::blocking::Client::new()
+    }
+
+    fn fetch(
+        &self,
+        client: &reqwest::blocking::Client,
+        repo: &GiteaRepo,
+    ) -> Result<Vec<RepoStatus>, String> {
+        let runs = fetch_workflow_runs(client, &self.api_base_url, &self.token_env, repo)?;
+        Ok(repo_status_from_runs(repo, &runs).into_iter().collect())
+    }
+
+    fn reports(&self, repo: &GiteaRepo, status: &RepoStatus) -> bool {
+        repo.reports(status)
+    }
+
+    fn error_status(&self, repo: &GiteaRepo) -> RepoStatus {
+        let mut status =
+            RepoStatus::new("gitea", &repo.name, &repo.main_branch, StateSummary::Error);
+        status.workflow = repo.workflow.clone();
+        repo.options.apply(&mut status);
+        status
+    }
+
+    fn host(&self) -> Option<&str> {
+        Some(&self.api_base_url)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkflowRuns {
+    workflow_runs: Vec<WorkflowRun>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkflowRun {
+    id: Option<u64>,
+    status: String,
+    conclusion: Option<String>,
+    html_url: Option<String>,
+    /// The workflow file and ref of the run, e.g. `ci.yml@refs/heads/main`.
+    path: Option<String>,
+    started_at: Option<String>,
+    completed_at: Option<String>,
+}
+
+impl WorkflowRun {
+    fn workflow(&self) -> Option<&str> {
+        let path = self.path.as_deref()?;
+        Some(path.split_once('@').map_or(path, |(workflow, _)| workflow))
+    }
+}
+
+fn fetch_workflow_runs(
+    client: &reqwest::blocking::Client,
+    api_base_url: &str,
+    token_env: &str,
+    repo: &GiteaRepo,
+) -> Result<Vec<WorkflowRun>, String> {
+    let token = std::env::var(token_env).map_err(|_| format!("Missing env var {}", token_env))?;
+    let (owner, repo_name) = split_repo_name(&repo.name)?;
+    let branch = urlencoding::encode(&repo.main_branch);
+    let url = format!(
+        "{}/repos/{}/{}/actions/runs?branch={}&limit={}",
+        api_base_url, owner, repo_name, branch, RUNS_PER_PAGE
+    );
+
+    let response = client
+        .get(url)
+        .header("Authorization", format!("token {}", token))
+        .header("Accept", "application/json")
+        .header("User-Agent", "pmon")
+        .send()
+        .map_err(|error| error.to_string())?;
+
+    let status = response.status();
+    let body = response.text().map_err(|error| error.to_string())?;
+
+    log::info!("Gitea API request completed: status {}", status);
+    log::debug!("Gitea API response: {}", body);
+
+    if !status.is_success() {
+        return Err(format!("Gitea API returned {}", status));
+    }
+
+    let runs = serde_json::from_str::<WorkflowRuns>(&body).map_err(|error| error.to_string())?;
+    Ok(runs
+        .workflow_runs
+        .into_iter()
+        .filter(|run| match &repo.workflow {
+            Some(workflow) => run.workflow() == Some(workflow.as_str()),
+            None => true,
+        })
+        .collect())
+}
+
+fn split_repo_name(name: &str) -> Result<(String, String), String> {
+    let (owner, repo) = name
+        .split_once('/')
+        .ok_or_else(|| format!("Invalid Gitea repo name: {}", name))?;
+    Ok((
+        urlencoding::encode(owner).into_owned(),
+        urlencoding::encode(repo).into_owned(),
+    ))
+}
+
+fn repo_status_from_runs(repo: &GiteaRepo, runs: &[WorkflowRun]) -> Option<RepoStatus> {
+    let state = state_from_runs(runs)?;
+    let latest = runs.first()?;
+    let mut status = RepoStatus::new("gitea", &repo.name, &repo.main_branch, state);
+    status.workflow = repo.workflow.clone();
+    repo.options.apply(&mut status);
+    status.run_id = latest.id;
+    status.url = latest.html_url.clone();
+    status.started_at = latest.started_at.clone();
+    status.updated_at = latest
+        .completed_at
+        .clone()
+        .or_else(|| latest.started_at.clone());
+    Some(status)
+}
+
+fn state_from_runs(runs: &[WorkflowRun]) -> Option<StateSummary> {
+    let latest = runs.first()?;
+
+    match latest.status.as_str() {
+        "completed" => conclusion_state(latest.conclusion.as_deref()),
+        "queued" | "in_progress" | "waiting" | "pending" | "requested" => {
+            Some(pending_state_from_history(runs))
+        }
+        _ => None,
+    }
+}
+
+fn conclusion_state(conclusion: Option<&str>) -> Option<StateSummary> {
+    match conclusion {
+        Some("success") => Some(StateSummary::Ok),
+        Some("failure") | Some("cancelled") | Some("timed_out") | Some("skipped")
+        | Some("blocked") => Some(StateSummary::Failure),
+        _ => None,
+    }
+}
+
+fn pending_state_from_history(runs: &[WorkflowRun]) -> StateSummary {
+    for run in runs.iter().skip(1) {
+        if run.status != "completed" {
+            continue;
+        }
+        match conclusion_state(run.conclusion.as_deref()) {
+            Some(StateSummary::Failure) => return StateSummary::FailurePending,
+            Some(StateSummary::Ok) => return StateSummary::OkPending,
+            _ => {}
+        }
+    }
+
+    StateSummary::OkPending
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::Server;
+
+    use super::{GiteaProvider, GiteaRepo};
+    use crate::core::StateSummary;
+    use crate::data_providers::test_support::{mock_json, poll_once, set_token};
+
+    #[test]
+    fn reports_failure_when_latest_failed() {
+        let mut server = Server::new();
+        set_token("GITEA_TOKEN", "test-token");
+
+        let _mock = server
+            .mock("GET", "/api/v1/repos/org/repo/actions/runs")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("branch".into(), "main".into()),
+                mockito::Matcher::UrlEncoded("limit".into(), "20".into()),
+            ]))
+            .match_header("authorization", "token test-token")
+            .with_status(200)
+            .with_body(
+                r#"{"workflow_runs":[{"status":"completed","conclusion":"failure"},{"status":"completed","conclusion":"success"}],"total_count":2}"#,
+            )
+            .create();
+
+        let repo = GiteaRepo::new("org/repo".to_string(), "main".to_string());
+        let statuses = poll_once(GiteaProvider::new(
+            &server.url(),
+            "GITEA_TOKEN".to_string(),
+            vec![repo],
+        ));
+
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].state, StateSummary::Failure);
+    }
+
+    #[test]
+    fn only_considers_runs_of_the_configured_workflow() {
+        let mut server = Server::new();
+        set_token("GITEA_TOKEN", "test-token");
+        let _mock = mock_json(
+            &mut server,
+            "/api/v1/repos/org/repo/actions/runs",
+            r#"{"workflow_runs":[
+                {"id":3,"status":"completed","conclusion":"failure","path":"lint.yml@refs/heads/main"},
+                {"id":2,"status":"in_progress","conclusion":null,"path":"ci.yml@refs/heads/main","html_url":"https://forgejo.example.com/org/repo/actions/runs/2"},
+                {"id":1,"status":"completed","conclusion":"success","path":"ci.yml@refs/heads/main"}
+            ],"total_count":3}"#,
+        );
+
+        let repo = GiteaRepo::new("org/repo".to_string(), "main".to_string())
+            .with_workflow(Some("ci.yml".to_string()));
+        let statuses = poll_once(GiteaProvider::new(
+            &format!("{}/", server.url()),
+            "GITEA_TOKEN".to_string(),
+            vec![repo],
+        ));
+
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].provider, "gitea");
+        assert_eq!(statuses[0].workflow.as_deref(), Some("ci.yml"));
+        assert_eq!(statuses[0].state, StateSummary::OkPending);
+        assert_eq!(statuses[0].run_id, Some(2));
+        assert_eq!(
+            statuses[0].url.as_deref(),
+            Some("https://forgejo.example.com/org/repo/actions/runs/2")
+        );
+    }
+}
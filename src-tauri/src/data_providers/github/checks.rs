@@ -0,0 +1,180 @@
+use serde::Deserialize;
+
+use super::{github_get, GithubRepo};
+use crate::core::{RepoStatus, StateSummary};
+use crate::data_providers::ResponseCache;
+
+#[derive(Debug, Deserialize)]
+struct Commit {
+    sha: String,
+    html_url: Option<String>,
+    #[serde(default)]
+    parents: Vec<ParentCommit>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ParentCommit {
+    sha: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CheckRuns {
+    check_runs: Vec<CheckRun>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CheckRun {
+    status: String,
+    conclusion: Option<String>,
+    started_at: Option<String>,
+    completed_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CombinedStatus {
+    state: String,
+    statuses: Vec<CommitStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommitStatus {
+    created_at: Option<String>,
+    updated_at: Option<String>,
+}
+
+/// Aggregates the check runs and commit statuses of the head commit of the branch.
+pub(super) fn fetch_commit_status(
+    client: &reqwest::blocking::Client,
+    cache: &ResponseCache,
+    repo_url: &str,
+    token_env: &str,
+    repo: &GithubRepo,
+) -> Result<Option<RepoStatus>, String> {
+    let branch = urlencoding::encode(&repo.main_branch);
+    let commit_url = format!("{}/commits/{}", repo_url, branch);
+    let commit: Commit = github_get(client, cache, &commit_url, &commit_url, token_env)?;
+    let (check_runs, combined) =
+        fetch_checks(client, cache, repo_url, token_env, &commit_url, &commit.sha)?;
+
+    let Some(result) = CheckResult::of(&check_runs.check_runs, &combined) else {
+        return Ok(None);
+    };
+    let state = if result.running {
+        // Like for workflow runs, a pending state shows the result of the previous commit.
+        let previous_failed = match commit.parents.first() {
+            Some(parent) => {
+                let (check_runs, combined) = fetch_checks(
+                    client,
+                    cache,
+                    repo_url,
+                    token_env,
+                    &format!("{} parent", commit_url),
+                    &parent.sha,
+                )?;
+                CheckResult::of(&check_runs.check_runs, &combined)
+                    .is_some_and(|previous| previous.failed)
+            }
+            None => false,
+        };
+        if previous_failed {
+            StateSummary::FailurePending
+        } else {
+            StateSummary::OkPending
+        }
+    } else if result.failed {
+        StateSummary::Failure
+    } else {
+        StateSummary::Ok
+    };
+    let mut status = RepoStatus::new("github", &repo.name, &repo.main_branch, state);
+    status.show_in_tray = repo.show_in_tray;
+    status.notifications = repo.notifications.clone();
+    // Checks have no run of their own, the commit identifies what was checked.
+    status.detail = Some(format!(
+        "Commit {}",
+        commit.sha.get(..7).unwrap_or(&commit.sha)
+    ));
+    status.url = commit.html_url;
+    status.started_at = check_runs
+        .check_runs
+        .iter()
+        .filter_map(|run| run.started_at.clone())
+        .chain(
+            combined
+                .statuses
+                .iter()
+                .filter_map(|commit_status| commit_status.created_at.clone()),
+        )
+        .min();
+    status.updated_at = check_runs
+        .check_runs
+        .iter()
+        .filter_map(|run| run.completed_at.clone())
+        .chain(
+            combined
+                .statuses
+                .iter()
+                .filter_map(|commit_status| commit_status.updated_at.clone()),
+        )
+        .max();
+    Ok(Some(status))
+}
+
+/// Fetches the check runs and commit statuses of a commit.
+///
+/// The responses are cached per `commit_slot` instead of per SHA, so the responses for a new
+/// commit replace those of the previous one.
+fn fetch_checks(
+    client: &reqwest::blocking::Client,
+    cache: &ResponseCache,
+    repo_url: &str,
+    token_env: &str,
+    commit_slot: &str,
+    sha: &str,
+) -> Result<(CheckRuns, CombinedStatus), String> {
+    let check_runs = github_get(
+        client,
+        cache,
+        &format!("{} check-runs", commit_slot),
+        &format!("{}/commits/{}/check-runs?per_page=100", repo_url, sha),
+        token_env,
+    )?;
+    let combined = github_get(
+        client,
+        cache,
+        &format!("{} status", commit_slot),
+        &format!("{}/commits/{}/status", repo_url, sha),
+        token_env,
+    )?;
+    Ok((check_runs, combined))
+}
+
+/// Whether any check of a commit is still running and whether any has failed.
+struct CheckResult {
+    running: bool,
+    failed: bool,
+}
+
+impl CheckResult {
+    fn of(check_runs: &[CheckRun], combined: &CombinedStatus) -> Option<Self> {
+        // The combined state is "pending" when no status was reported, ignore it then.
+        let has_statuses = !combined.statuses.is_empty();
+        if check_runs.is_empty() && !has_statuses {
+            return None;
+        }
+
+        let failed = check_runs.iter().any(|run| {
+            matches!(
+                run.conclusion.as_deref(),
+                Some("failure")
+                    | Some("cancelled")
+                    | Some("timed_out")
+                    | Some("action_required")
+                    | Some("startup_failure")
+            )
+        }) || (has_statuses && matches!(combined.state.as_str(), "failure" | "error"));
+        let running = check_runs.iter().any(|run| run.status != "completed")
+            || (has_statuses && combined.state == "pending");
+        Some(Self { running, failed })
+    }
+}
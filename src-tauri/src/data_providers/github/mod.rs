@@ -1,41 +1,101 @@
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
-};
-use std::thread::{self, JoinHandle};
+use std::path::PathBuf;
 use std::time::Duration;
 
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 
-use crate::core::{DataProvider, StateSummary, StateSummaryAdapter, StateSummaryGateway};
+use crate::core::config::{GithubMode, NotificationConfig};
+use crate::core::{RepoStatus, StateSummary, StatusFetcher};
+use crate::data_providers::{http_client, ResponseCache};
 
 const DEFAULT_GITHUB_API_BASE_URL: &str = "https://api.github.com";
-const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(60);
+// Runs of all workflows are listed together, fetch enough to cover every workflow of a branch.
+const REPO_RUNS_PER_PAGE: usize = 100;
+
+mod checks;
+mod rate_limit;
+
+pub use rate_limit::{rate_limits, RateLimit};
 
 #[derive(Clone)]
 pub struct GithubRepo {
     name: String,
     main_branch: String,
-    workflow: String,
+    /// Workflow file names or globs, all workflows are monitored if empty.
+    workflows: Vec<String>,
+    mode: GithubMode,
+    show_in_tray: bool,
+    notifications: NotificationConfig,
 }
 
 impl GithubRepo {
     pub fn new(name: String, main_branch: String, workflow: String) -> Self {
+        Self::new_with_workflows(name, main_branch, vec![workflow])
+    }
+
+    pub fn new_with_workflows(name: String, main_branch: String, workflows: Vec<String>) -> Self {
         Self {
             name,
             main_branch,
-            workflow,
+            workflows,
+            mode: GithubMode::Actions,
+            show_in_tray: true,
+            notifications: NotificationConfig::default(),
         }
     }
+
+    pub fn with_mode(mut self, mode: GithubMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_show_in_tray(mut self, show_in_tray: bool) -> Self {
+        self.show_in_tray = show_in_tray;
+        self
+    }
+
+    pub fn with_notifications(mut self, notifications: NotificationConfig) -> Self {
+        self.notifications = notifications;
+        self
+    }
+
+    fn reports(&self, status: &RepoStatus) -> bool {
+        status.provider == "github"
+            && status.repo == self.name
+            && status.branch == self.main_branch
+            && match self.mode {
+                GithubMode::Actions => status
+                    .workflow
+                    .as_deref()
+                    .is_some_and(|workflow| self.monitors_workflow(workflow)),
+                GithubMode::Checks => status.workflow.is_none(),
+            }
+    }
+
+    /// The workflow, if exactly one is monitored and it can be queried directly.
+    fn single_workflow(&self) -> Option<&str> {
+        match self.workflows.as_slice() {
+            [workflow] if !is_glob(workflow) => Some(workflow),
+            _ => None,
+        }
+    }
+
+    fn monitors_workflow(&self, workflow: &str) -> bool {
+        self.workflows.is_empty()
+            || self
+                .workflows
+                .iter()
+                .any(|pattern| glob_matches(pattern, workflow))
+    }
 }
 
 pub struct GithubProvider {
     token_env: String,
     repos: Vec<GithubRepo>,
     api_base_url: String,
-    poll_interval: Duration,
-    stop_signal: Arc<AtomicBool>,
-    thread_handle: Option<JoinHandle<()>>,
+    ca_cert: Option<PathBuf>,
+    cache: ResponseCache,
 }
 
 impl GithubProvider {
@@ -51,119 +111,81 @@ impl GithubProvider {
         Self {
             token_env,
             repos,
-            api_base_url,
-            poll_interval: DEFAULT_POLL_INTERVAL,
-            stop_signal: Arc::new(AtomicBool::new(false)),
-            thread_handle: None,
+            // Enterprise Server URLs end in /api/v3, paths are appended with a leading slash.
+            api_base_url: api_base_url.trim_end_matches('/').to_string(),
+            ca_cert: None,
+            cache: ResponseCache::new(),
         }
     }
 
-    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
-        self.poll_interval = poll_interval;
+    pub fn with_ca_cert(mut self, ca_cert: Option<PathBuf>) -> Self {
+        self.ca_cert = ca_cert;
         self
     }
+}
 
-    fn poll_once(&self, client: &reqwest::blocking::Client, gateway: &StateSummaryGateway) {
-        let mut combined: Option<StateSummary> = None;
-
-        for repo in &self.repos {
-            eprintln!(
-                "GitHub provider polling {} on {}",
-                repo.name, repo.main_branch
-            );
-            match fetch_workflow_runs(client, &self.api_base_url, &self.token_env, repo) {
-                Ok(runs) => {
-                    eprintln!(
-                        "GitHub provider received {} runs for {}",
-                        runs.len(),
-                        repo.name
-                    );
-                    if let Some(summary) = state_from_runs(&runs) {
-                        eprintln!("GitHub provider summary for {} is {:?}", repo.name, summary);
-                        combined = Some(combine_summaries(combined, summary));
-                    }
-                }
-                Err(error) => {
-                    eprintln!("GitHub provider error for {}: {}", repo.name, error);
-                }
-            }
-        }
+impl StatusFetcher for GithubProvider {
+    type Repo = GithubRepo;
+    /// Building the client fails for an unusable `ca_cert`, which is reported as a fetch error.
+    type Client = Result<reqwest::blocking::Client, String>;
 
-        if let Some(summary) = combined {
-            gateway.set_state_summary(summary);
-        }
+    fn name(&self) -> &'static str {
+        "GitHub"
     }
-}
 
-impl DataProvider for GithubProvider {
-    fn refresh(&mut self) {
-        let client = reqwest::blocking::Client::new();
-        let gateway = StateSummaryGateway::new();
-        self.poll_once(&client, &gateway);
+    fn repos(&self) -> &[GithubRepo] {
+        &self.repos
     }
 
-    fn start(&mut self, state_summary_gateway: Arc<StateSummaryGateway>) {
-        eprintln!("Starting GitHub provider");
-        if self.thread_handle.is_some() {
-            return;
-        }
-
-        let repos = self.repos.clone();
-        let api_base_url = self.api_base_url.clone();
-        let token_env = self.token_env.clone();
-        let poll_interval = self.poll_interval;
-        let stop_signal = self.stop_signal.clone();
+    fn describe(&self, repo: &GithubRepo) -> String {
+        format!("{} on {}", repo.name, repo.main_branch)
+    }
 
-        self.thread_handle = Some(thread::spawn(move || {
-            let client = reqwest::blocking::Client::new();
+    fn client(&self) -> Self::Client {
+        http_client(self.ca_cert.as_deref(), false)
+    }
 
-            loop {
-                if stop_signal.load(Ordering::Relaxed) {
-                    break;
-                }
+    fn fetch(&self, client: &Self::Client, repo: &GithubRepo) -> Result<Vec<RepoStatus>, String> {
+        let client = client.as_ref().map_err(Clone::clone)?;
+        fetch_repo_statuses(
+            client,
+            &self.cache,
+            &self.api_base_url,
+            &self.token_env,
+            repo,
+        )
+    }
 
-                let mut combined: Option<StateSummary> = None;
-                for repo in &repos {
-                    eprintln!(
-                        "GitHub provider polling {} on {}",
-                        repo.name, repo.main_branch
-                    );
-                    match fetch_workflow_runs(&client, &api_base_url, &token_env, repo) {
-                        Ok(runs) => {
-                            eprintln!(
-                                "GitHub provider received {} runs for {}",
-                                runs.len(),
-                                repo.name
-                            );
-                            if let Some(summary) = state_from_runs(&runs) {
-                                eprintln!(
-                                    "GitHub provider summary for {} is {:?}",
-                                    repo.name, summary
-                                );
-                                combined = Some(combine_summaries(combined, summary));
-                            }
-                        }
-                        Err(error) => {
-                            eprintln!("GitHub provider error for {}: {}", repo.name, error);
-                        }
-                    }
-                }
+    fn reports(&self, repo: &GithubRepo, status: &RepoStatus) -> bool {
+        repo.reports(status)
+    }
 
-                if let Some(summary) = combined {
-                    eprintln!("GitHub provider combined summary is {:?}", summary);
-                    state_summary_gateway.set_state_summary(summary);
-                }
+    fn error_status(&self, repo: &GithubRepo) -> RepoStatus {
+        let mut status =
+            RepoStatus::new("github", &repo.name, &repo.main_branch, StateSummary::Error);
+        if repo.mode == GithubMode::Actions {
+            status.workflow = Some(repo.workflows.join(", "));
+        }
+        status.show_in_tray = repo.show_in_tray;
+        status.notifications = repo.notifications.clone();
+        status
+    }
 
-                sleep_with_stop(&stop_signal, poll_interval);
-            }
-        }));
+    fn host(&self) -> Option<&str> {
+        Some(&self.api_base_url)
     }
 
-    fn stop(&mut self) {
-        self.stop_signal.store(true, Ordering::Relaxed);
-        if let Some(handle) = self.thread_handle.take() {
-            let _ = handle.join();
-        }
+    fn poll_delay(&self, poll_interval: Duration) -> Duration {
+        let requests_per_poll = self
+            .repos
+            .iter()
+            .map(|repo| match repo.mode {
+                GithubMode::Actions => 1,
+                // The head commit and its checks, plus the checks of the parent while running.
+                GithubMode::Checks => 5,
+            })
+            .sum();
+        rate_limit::poll_delay(&self.token_env, poll_interval, requests_per_poll)
     }
 }
 
@@ -174,24 +196,123 @@ struct WorkflowRuns {
 
 #[derive(Debug, Deserialize)]
 struct WorkflowRun {
+    id: Option<u64>,
+    workflow_id: Option<u64>,
+    /// The workflow file of the run, e.g. `.github/workflows/ci.yml`.
+    path: Option<String>,
     status: String,
     conclusion: Option<String>,
+    html_url: Option<String>,
+    run_started_at: Option<String>,
+    updated_at: Option<String>,
 }
 
-fn fetch_workflow_runs(
+/// Fetches the status of every monitored workflow of the repo.
+fn fetch_repo_statuses(
     client: &reqwest::blocking::Client,
+    cache: &ResponseCache,
     api_base_url: &str,
     token_env: &str,
     repo: &GithubRepo,
-) -> Result<Vec<WorkflowRun>, String> {
-    let token = std::env::var(token_env).map_err(|_| format!("Missing env var {}", token_env))?;
+) -> Result<Vec<RepoStatus>, String> {
     let (owner, repo_name) = split_repo_name(&repo.name)?;
-    let workflow = urlencoding::encode(&repo.workflow);
     let branch = urlencoding::encode(&repo.main_branch);
+
+    if repo.mode == GithubMode::Checks {
+        let repo_url = format!("{}/repos/{}/{}", api_base_url, owner, repo_name);
+        return Ok(
+            checks::fetch_commit_status(client, cache, &repo_url, token_env, repo)?
+                .into_iter()
+                .collect(),
+        );
+    }
+
+    if let Some(workflow) = repo.single_workflow() {
+        let url = format!(
+            "{}/repos/{}/{}/actions/workflows/{}/runs?branch={}&per_page=2",
+            api_base_url,
+            owner,
+            repo_name,
+            urlencoding::encode(workflow),
+            branch
+        );
+        let runs = fetch_workflow_runs(client, cache, &url, token_env)?;
+        eprintln!(
+            "GitHub provider received {} runs for {}",
+            runs.len(),
+            repo.name
+        );
+        return Ok(repo_status_from_runs(repo, workflow, &runs)
+            .into_iter()
+            .collect());
+    }
+
     let url = format!(
-        "{}/repos/{}/{}/actions/workflows/{}/runs?branch={}&per_page=2",
-        api_base_url, owner, repo_name, workflow, branch
+        "{}/repos/{}/{}/actions/runs?branch={}&per_page={}",
+        api_base_url, owner, repo_name, branch, REPO_RUNS_PER_PAGE
     );
+    let runs = fetch_workflow_runs(client, cache, &url, token_env)?;
+    eprintln!(
+        "GitHub provider received {} runs for {}",
+        runs.len(),
+        repo.name
+    );
+    Ok(group_runs_by_workflow(runs)
+        .into_iter()
+        .filter(|(workflow, _)| repo.monitors_workflow(workflow))
+        .filter_map(|(workflow, runs)| repo_status_from_runs(repo, &workflow, &runs))
+        .collect())
+}
+
+/// Groups runs by the file name of their workflow, keeping the newest first order.
+fn group_runs_by_workflow(runs: Vec<WorkflowRun>) -> Vec<(String, Vec<WorkflowRun>)> {
+    let mut groups: Vec<(Option<u64>, String, Vec<WorkflowRun>)> = Vec::new();
+    for run in runs {
+        let workflow = workflow_file_name(&run);
+        match groups
+            .iter_mut()
+            .find(|(id, name, _)| *id == run.workflow_id && *name == workflow)
+        {
+            Some((_, _, group)) => group.push(run),
+            None => groups.push((run.workflow_id, workflow, vec![run])),
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(_, workflow, runs)| (workflow, runs))
+        .collect()
+}
+
+fn workflow_file_name(run: &WorkflowRun) -> String {
+    match &run.path {
+        Some(path) => path.rsplit('/').next().unwrap_or(path).to_string(),
+        None => run.workflow_id.map(|id| id.to_string()).unwrap_or_default(),
+    }
+}
+
+fn fetch_workflow_runs(
+    client: &reqwest::blocking::Client,
+    cache: &ResponseCache,
+    url: &str,
+    token_env: &str,
+) -> Result<Vec<WorkflowRun>, String> {
+    // The URL only depends on the config, so it names the slot.
+    let runs: WorkflowRuns = github_get(client, cache, url, url, token_env)?;
+    Ok(runs.workflow_runs)
+}
+
+/// Sends an authenticated GET request to the GitHub API and deserializes the response.
+///
+/// The response is cached in `cache_slot`, see [`ResponseCache`].
+fn github_get<T: DeserializeOwned>(
+    client: &reqwest::blocking::Client,
+    cache: &ResponseCache,
+    cache_slot: &str,
+    url: &str,
+    token_env: &str,
+) -> Result<T, String> {
+    let token = std::env::var(token_env).map_err(|_| format!("Missing env var {}", token_env))?;
+    rate_limit::check_paused(token_env)?;
 
     if github_debug_enabled() {
         eprintln!(
@@ -200,11 +321,13 @@ fn fetch_workflow_runs(
         );
     }
 
-    let response = client
+    let request = client
         .get(url)
         .header("Authorization", format!("Bearer {}", token))
         .header("Accept", "application/vnd.github+json")
-        .header("User-Agent", "pmon")
+        .header("User-Agent", "pmon");
+    let response = cache
+        .conditional(cache_slot, url, request)
         .send()
         .map_err(|error| error.to_string())?;
 
@@ -215,7 +338,9 @@ fn fetch_workflow_runs(
         .and_then(|value| value.to_str().ok())
         .unwrap_or("unknown")
         .to_string();
+    let headers = response.headers().clone();
     let body = response.text().map_err(|error| error.to_string())?;
+    rate_limit::record_response(token_env, status, &headers, &body);
 
     log::info!(
         "GitHub API request completed: status {} request_id {}",
@@ -224,15 +349,15 @@ fn fetch_workflow_runs(
     );
     log::debug!("GitHub API response: {}", body);
 
-    if !status.is_success() {
+    if !status.is_success() && status != StatusCode::NOT_MODIFIED {
         return Err(format!(
             "GitHub API returned {} (request_id {})",
             status, request_id
         ));
     }
 
-    let runs = serde_json::from_str::<WorkflowRuns>(&body).map_err(|error| error.to_string())?;
-    Ok(runs.workflow_runs)
+    let body = cache.resolve(cache_slot, url, status, &headers, body)?;
+    serde_json::from_str::<T>(&body).map_err(|error| error.to_string())
 }
 
 fn github_debug_enabled() -> bool {
@@ -252,6 +377,57 @@ fn split_repo_name(name: &str) -> Result<(String, String), String> {
     ))
 }
 
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+/// Matches `text` against a glob with `*` (any characters) and `?` (one character).
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+fn repo_status_from_runs(
+    repo: &GithubRepo,
+    workflow: &str,
+    runs: &[WorkflowRun],
+) -> Option<RepoStatus> {
+    let state = state_from_runs(runs)?;
+    let latest = runs.first()?;
+    let mut status = RepoStatus::new("github", &repo.name, &repo.main_branch, state);
+    status.workflow = Some(workflow.to_string());
+    status.show_in_tray = repo.show_in_tray;
+    status.notifications = repo.notifications.clone();
+    status.run_id = latest.id;
+    status.url = latest.html_url.clone();
+    status.started_at = latest.run_started_at.clone();
+    status.updated_at = latest.updated_at.clone();
+    Some(status)
+}
+
 fn state_from_runs(runs: &[WorkflowRun]) -> Option<StateSummary> {
     let latest = runs.first()?;
 
@@ -293,50 +469,17 @@ fn pending_state_from_history(runs: &[WorkflowRun]) -> StateSummary {
     StateSummary::OkPending
 }
 
-fn combine_summaries(current: Option<StateSummary>, next: StateSummary) -> StateSummary {
-    match current {
-        None => next,
-        Some(existing) => {
-            if summary_rank(next) > summary_rank(existing) {
-                next
-            } else {
-                existing
-            }
-        }
-    }
-}
-
-fn summary_rank(summary: StateSummary) -> u8 {
-    match summary {
-        StateSummary::Ok => 0,
-        StateSummary::OkPending => 1,
-        StateSummary::FailurePending => 2,
-        StateSummary::Failure => 3,
-    }
-}
-
-fn sleep_with_stop(stop_signal: &AtomicBool, duration: Duration) {
-    let mut remaining = duration;
-    let tick = Duration::from_secs(1);
-    while remaining > Duration::ZERO {
-        if stop_signal.load(Ordering::Relaxed) {
-            break;
-        }
-        let sleep_for = if remaining < tick { remaining } else { tick };
-        thread::sleep(sleep_for);
-        remaining = remaining.saturating_sub(sleep_for);
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use std::sync::{Arc, Mutex};
-    use std::time::Duration;
 
     use mockito::Server;
 
-    use super::{GithubProvider, GithubRepo};
-    use crate::core::{StateSummary, StateSummaryAdapter, StateSummaryGateway};
+    use super::{glob_matches, GithubProvider, GithubRepo};
+    use crate::core::config::GithubMode;
+    use crate::core::{
+        Poller, StateSummary, StateSummaryAdapter, StateSummaryGateway, StatusFetcher,
+    };
 
     struct TestSummaryAdapter {
         latest: Arc<Mutex<Option<StateSummary>>>,
@@ -377,8 +520,7 @@ mod tests {
             "build.yml".to_string(),
         );
         let provider =
-            GithubProvider::new_with_base_url("GITHUB_TOKEN".to_string(), vec![repo], server.url())
-                .with_poll_interval(Duration::from_secs(1));
+            GithubProvider::new_with_base_url("GITHUB_TOKEN".to_string(), vec![repo], server.url());
 
         let gateway = StateSummaryGateway::new();
         let latest = Arc::new(Mutex::new(None));
@@ -386,7 +528,7 @@ mod tests {
             latest: latest.clone(),
         }));
 
-        provider.poll_once(&reqwest::blocking::Client::new(), &gateway);
+        Poller::new(provider).poll_once(&Ok(reqwest::blocking::Client::new()), &gateway);
 
         assert_eq!(*latest.lock().unwrap(), Some(StateSummary::Failure));
     }
@@ -426,8 +568,294 @@ mod tests {
             latest: latest.clone(),
         }));
 
-        provider.poll_once(&reqwest::blocking::Client::new(), &gateway);
+        Poller::new(provider).poll_once(&Ok(reqwest::blocking::Client::new()), &gateway);
 
         assert_eq!(*latest.lock().unwrap(), Some(StateSummary::OkPending));
     }
+
+    #[test]
+    fn publishes_repo_status_with_run_details() {
+        let mut server = Server::new();
+        let token = "test-token";
+        unsafe {
+            std::env::set_var("GITHUB_TOKEN", token);
+        }
+
+        let _mock = server
+            .mock("GET", "/repos/org/repo/actions/workflows/build.yml/runs")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body(
+                r#"{"workflow_runs":[{"id":42,"status":"completed","conclusion":"success","html_url":"https://github.com/org/repo/actions/runs/42","run_started_at":"2025-01-01T10:00:00Z","updated_at":"2025-01-01T10:05:00Z"}]}"#,
+            )
+            .create();
+
+        let repo = GithubRepo::new(
+            "org/repo".to_string(),
+            "main".to_string(),
+            "build.yml".to_string(),
+        );
+        let provider =
+            GithubProvider::new_with_base_url("GITHUB_TOKEN".to_string(), vec![repo], server.url());
+
+        let gateway = StateSummaryGateway::new();
+        Poller::new(provider).poll_once(&Ok(reqwest::blocking::Client::new()), &gateway);
+
+        let statuses = gateway.repo_statuses();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].repo, "org/repo");
+        assert_eq!(statuses[0].workflow.as_deref(), Some("build.yml"));
+        assert_eq!(statuses[0].state, StateSummary::Ok);
+        assert_eq!(statuses[0].run_id, Some(42));
+        assert_eq!(
+            statuses[0].url.as_deref(),
+            Some("https://github.com/org/repo/actions/runs/42")
+        );
+    }
+
+    #[test]
+    fn publishes_a_status_per_matching_workflow() {
+        let mut server = Server::new();
+        let token = "test-token";
+        unsafe {
+            std::env::set_var("GITHUB_TOKEN", token);
+        }
+
+        let _mock = server
+            .mock("GET", "/repos/org/repo/actions/runs")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("branch".into(), "main".into()),
+                mockito::Matcher::UrlEncoded("per_page".into(), "100".into()),
+            ]))
+            .match_header("authorization", format!("Bearer {}", token).as_str())
+            .with_status(200)
+            .with_body(
+                r#"{"workflow_runs":[
+                    {"id":5,"workflow_id":1,"path":".github/workflows/build.yml","status":"in_progress","conclusion":null},
+                    {"id":4,"workflow_id":2,"path":".github/workflows/deploy-staging.yml","status":"completed","conclusion":"failure"},
+                    {"id":3,"workflow_id":3,"path":".github/workflows/lint.yml","status":"completed","conclusion":"success"},
+                    {"id":2,"workflow_id":1,"path":".github/workflows/build.yml","status":"completed","conclusion":"failure"}
+                ]}"#,
+            )
+            .create();
+
+        let repo = GithubRepo::new_with_workflows(
+            "org/repo".to_string(),
+            "main".to_string(),
+            vec!["build.yml".to_string(), "deploy-*.yml".to_string()],
+        );
+        let provider =
+            GithubProvider::new_with_base_url("GITHUB_TOKEN".to_string(), vec![repo], server.url());
+
+        let gateway = StateSummaryGateway::new();
+        Poller::new(provider).poll_once(&Ok(reqwest::blocking::Client::new()), &gateway);
+
+        let statuses: Vec<(Option<String>, StateSummary)> = gateway
+            .repo_statuses()
+            .into_iter()
+            .map(|status| (status.workflow, status.state))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                (Some("build.yml".to_string()), StateSummary::FailurePending),
+                (
+                    Some("deploy-staging.yml".to_string()),
+                    StateSummary::Failure
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn supports_enterprise_server_base_url() {
+        let mut server = Server::new();
+        unsafe {
+            std::env::set_var("PMON_TEST_GHES_TOKEN", "token");
+        }
+
+        let _mock = server
+            .mock(
+                "GET",
+                "/api/v3/repos/org/repo/actions/workflows/build.yml/runs",
+            )
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body(r#"{"workflow_runs":[{"status":"completed","conclusion":"success"}]}"#)
+            .create();
+
+        let repo = GithubRepo::new(
+            "org/repo".to_string(),
+            "main".to_string(),
+            "build.yml".to_string(),
+        );
+        let provider = GithubProvider::new_with_base_url(
+            "PMON_TEST_GHES_TOKEN".to_string(),
+            vec![repo],
+            format!("{}/api/v3/", server.url()),
+        );
+
+        let gateway = StateSummaryGateway::new();
+        Poller::new(provider).poll_once(&Ok(reqwest::blocking::Client::new()), &gateway);
+
+        let statuses = gateway.repo_statuses();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].state, StateSummary::Ok);
+    }
+
+    #[test]
+    fn reports_an_unusable_ca_cert_as_fetch_error() {
+        let ca_cert = std::env::temp_dir().join(format!("pmon-ca-cert-{}.pem", std::process::id()));
+        std::fs::write(&ca_cert, "not a certificate").unwrap();
+        let repo = GithubRepo::new(
+            "org/repo".to_string(),
+            "main".to_string(),
+            "build.yml".to_string(),
+        );
+        let provider = GithubProvider::new("PMON_TEST_CA_CERT_TOKEN".to_string(), vec![repo])
+            .with_ca_cert(Some(ca_cert.clone()));
+
+        let gateway = StateSummaryGateway::new();
+        let client = provider.client();
+        Poller::new(provider).poll_once(&client, &gateway);
+        let _ = std::fs::remove_file(&ca_cert);
+
+        let statuses = gateway.repo_statuses();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].state, StateSummary::Error);
+        assert!(statuses[0]
+            .detail
+            .as_deref()
+            .is_some_and(|detail| detail.starts_with("Invalid certificate")));
+    }
+
+    #[test]
+    fn reuses_cached_runs_when_not_modified() {
+        let mut server = Server::new();
+        unsafe {
+            std::env::set_var("PMON_TEST_GITHUB_ETAG_TOKEN", "token");
+        }
+
+        let first = server
+            .mock("GET", "/repos/org/repo/actions/workflows/build.yml/runs")
+            .match_query(mockito::Matcher::Any)
+            .match_header("if-none-match", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_header("etag", "\"runs-v1\"")
+            .with_body(
+                r#"{"workflow_runs":[{"id":7,"status":"completed","conclusion":"failure"}]}"#,
+            )
+            .expect(1)
+            .create();
+        let second = server
+            .mock("GET", "/repos/org/repo/actions/workflows/build.yml/runs")
+            .match_query(mockito::Matcher::Any)
+            .match_header("if-none-match", "\"runs-v1\"")
+            .with_status(304)
+            .expect(1)
+            .create();
+
+        let repo = GithubRepo::new(
+            "org/repo".to_string(),
+            "main".to_string(),
+            "build.yml".to_string(),
+        );
+        let provider = Poller::new(GithubProvider::new_with_base_url(
+            "PMON_TEST_GITHUB_ETAG_TOKEN".to_string(),
+            vec![repo],
+            server.url(),
+        ));
+        let client = Ok(reqwest::blocking::Client::new());
+
+        provider.poll_once(&client, &StateSummaryGateway::new());
+        let gateway = StateSummaryGateway::new();
+        provider.poll_once(&client, &gateway);
+
+        first.assert();
+        second.assert();
+        let statuses = gateway.repo_statuses();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].state, StateSummary::Failure);
+        assert_eq!(statuses[0].run_id, Some(7));
+    }
+
+    #[test]
+    fn matches_workflow_globs() {
+        assert!(glob_matches("deploy-*.yml", "deploy-prod.yml"));
+        assert!(glob_matches("*", "ci.yml"));
+        assert!(glob_matches("ci.y?l", "ci.yml"));
+        assert!(!glob_matches("deploy-*.yml", "deploy-prod.yaml"));
+    }
+
+    #[test]
+    fn aggregates_check_runs_and_commit_statuses() {
+        let mut server = Server::new();
+        unsafe {
+            std::env::set_var("PMON_TEST_GITHUB_CHECKS_TOKEN", "token");
+        }
+
+        let _commit = server
+            .mock("GET", "/repos/octo/app/commits/main")
+            .with_status(200)
+            .with_body(
+                r#"{"sha":"0123456789abcdef0123","html_url":"https://example.com/c",
+                    "parents":[{"sha":"fedcba9876543210fedc"}]}"#,
+            )
+            .create();
+        let _checks = server
+            .mock("GET", "/repos/octo/app/commits/0123456789abcdef0123/check-runs")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body(
+                r#"{"check_runs":[
+                    {"status":"completed","conclusion":"success","completed_at":"2024-01-01T10:00:00Z"},
+                    {"status":"in_progress","conclusion":null}
+                ]}"#,
+            )
+            .create();
+        let _status = server
+            .mock("GET", "/repos/octo/app/commits/0123456789abcdef0123/status")
+            .with_status(200)
+            .with_body(r#"{"state":"success","statuses":[{"updated_at":"2024-01-01T11:00:00Z"}]}"#)
+            .create();
+        // The checks of the parent commit decide the pending state while checks are running.
+        let _parent_checks = server
+            .mock(
+                "GET",
+                "/repos/octo/app/commits/fedcba9876543210fedc/check-runs",
+            )
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body(r#"{"check_runs":[{"status":"completed","conclusion":"failure"}]}"#)
+            .create();
+        let _parent_status = server
+            .mock("GET", "/repos/octo/app/commits/fedcba9876543210fedc/status")
+            .with_status(200)
+            .with_body(r#"{"state":"pending","statuses":[]}"#)
+            .create();
+
+        let repo =
+            GithubRepo::new_with_workflows("octo/app".to_string(), "main".to_string(), vec![])
+                .with_mode(GithubMode::Checks);
+        let provider = GithubProvider::new_with_base_url(
+            "PMON_TEST_GITHUB_CHECKS_TOKEN".to_string(),
+            vec![repo],
+            server.url(),
+        );
+
+        let gateway = StateSummaryGateway::new();
+        Poller::new(provider).poll_once(&Ok(reqwest::blocking::Client::new()), &gateway);
+
+        let statuses = gateway.repo_statuses();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].workflow, None);
+        assert_eq!(statuses[0].state, StateSummary::FailurePending);
+        assert_eq!(statuses[0].run_id, None);
+        assert_eq!(statuses[0].detail.as_deref(), Some("Commit 0123456"));
+        assert_eq!(statuses[0].url.as_deref(), Some("https://example.com/c"));
+        assert_eq!(
+            statuses[0].updated_at.as_deref(),
+            Some("2024-01-01T11:00:00Z")
+        );
+    }
 }
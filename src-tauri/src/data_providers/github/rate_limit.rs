@@ -0,0 +1,224 @@
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde::Serialize;
+
+// GitHub asks to wait at least a minute after hitting a secondary rate limit without Retry-After.
+const DEFAULT_PAUSE: Duration = Duration::from_secs(60);
+
+/// The request budget of a GitHub token, taken from the headers of the latest response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RateLimit {
+    /// Name of the environment variable holding the token.
+    pub token_env: String,
+    pub limit: Option<u64>,
+    pub remaining: Option<u64>,
+    /// Unix timestamp in seconds when the budget is reset.
+    pub reset: Option<u64>,
+    /// Unix timestamp in seconds until which no requests are sent, after GitHub refused one.
+    pub paused_until: Option<u64>,
+}
+
+impl RateLimit {
+    fn new(token_env: &str) -> Self {
+        Self {
+            token_env: token_env.to_string(),
+            limit: None,
+            remaining: None,
+            reset: None,
+            paused_until: None,
+        }
+    }
+}
+
+// Providers using the same token share its budget, so it is tracked across providers.
+static RATE_LIMITS: Mutex<Vec<RateLimit>> = Mutex::new(Vec::new());
+
+/// The budgets of all GitHub tokens that received a response so far.
+pub fn rate_limits() -> Vec<RateLimit> {
+    RATE_LIMITS
+        .lock()
+        .map(|rate_limits| {
+            rate_limits
+                .iter()
+                .filter(|rate_limit| {
+                    rate_limit.limit.is_some() || rate_limit.paused_until.is_some()
+                })
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn with_rate_limit<T>(token_env: &str, update: impl FnOnce(&mut RateLimit) -> T) -> Option<T> {
+    let mut rate_limits = RATE_LIMITS.lock().ok()?;
+    let index = match rate_limits
+        .iter()
+        .position(|rate_limit| rate_limit.token_env == token_env)
+    {
+        Some(index) => index,
+        None => {
+            rate_limits.push(RateLimit::new(token_env));
+            rate_limits.len() - 1
+        }
+    };
+    Some(update(&mut rate_limits[index]))
+}
+
+/// Fails while the token is paused, so no request is wasted on a certain refusal.
+pub(super) fn check_paused(token_env: &str) -> Result<(), String> {
+    let now = unix_now();
+    match with_rate_limit(token_env, |rate_limit| rate_limit.paused_until).flatten() {
+        Some(paused_until) if paused_until > now => Err(format!(
+            "Rate limit of {} exceeded, paused for {}s",
+            token_env,
+            paused_until - now
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Updates the budget of the token from a response and pauses the token if GitHub refused the
+/// request because of a primary or secondary rate limit.
+pub(super) fn record_response(
+    token_env: &str,
+    status: StatusCode,
+    headers: &HeaderMap,
+    body: &str,
+) {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+    };
+    let limit = header("x-ratelimit-limit");
+    let remaining = header("x-ratelimit-remaining");
+    let reset = header("x-ratelimit-reset");
+    let retry_after = header("retry-after");
+    let now = unix_now();
+
+    with_rate_limit(token_env, |rate_limit| {
+        if limit.is_some() {
+            rate_limit.limit = limit;
+            rate_limit.remaining = remaining;
+            rate_limit.reset = reset;
+        }
+        let rate_limited = status == StatusCode::TOO_MANY_REQUESTS
+            || (status == StatusCode::FORBIDDEN
+                && (remaining == Some(0)
+                    || retry_after.is_some()
+                    || body.to_lowercase().contains("rate limit")));
+        rate_limit.paused_until = if rate_limited {
+            let until = match (retry_after, reset) {
+                (Some(retry_after), _) => now + retry_after,
+                (None, Some(reset)) if remaining == Some(0) && reset > now => reset,
+                _ => now + DEFAULT_PAUSE.as_secs(),
+            };
+            eprintln!(
+                "GitHub rate limit of {} exceeded, pausing for {}s",
+                token_env,
+                until - now
+            );
+            Some(until)
+        } else {
+            None
+        };
+    });
+}
+
+/// The delay until the next poll, stretched so the remaining budget lasts until the reset.
+pub(super) fn poll_delay(
+    token_env: &str,
+    poll_interval: Duration,
+    requests_per_poll: u64,
+) -> Duration {
+    let Some(rate_limit) = with_rate_limit(token_env, |rate_limit| rate_limit.clone()) else {
+        return poll_interval;
+    };
+    let now = unix_now();
+    if let Some(paused_until) = rate_limit.paused_until.filter(|until| *until > now) {
+        return poll_interval.max(Duration::from_secs(paused_until - now));
+    }
+    match (rate_limit.remaining, rate_limit.reset) {
+        (Some(remaining), Some(reset)) if reset > now => stretched_interval(
+            poll_interval,
+            remaining,
+            Duration::from_secs(reset - now),
+            requests_per_poll,
+        ),
+        _ => poll_interval,
+    }
+}
+
+fn stretched_interval(
+    poll_interval: Duration,
+    remaining: u64,
+    until_reset: Duration,
+    requests_per_poll: u64,
+) -> Duration {
+    if requests_per_poll == 0 {
+        return poll_interval;
+    }
+    let polls_left = remaining / requests_per_poll;
+    if polls_left == 0 {
+        return poll_interval.max(until_reset);
+    }
+    let polls_left = u32::try_from(polls_left).unwrap_or(u32::MAX);
+    poll_interval.max(until_reset / polls_left)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use reqwest::header::{HeaderMap, HeaderValue};
+    use reqwest::StatusCode;
+
+    use super::{check_paused, rate_limits, record_response, stretched_interval};
+
+    #[test]
+    fn stretches_interval_as_budget_shrinks() {
+        let minute = Duration::from_secs(60);
+        let hour = Duration::from_secs(3600);
+
+        assert_eq!(stretched_interval(minute, 5000, hour, 10), minute);
+        assert_eq!(
+            stretched_interval(minute, 300, hour, 10),
+            Duration::from_secs(120)
+        );
+        assert_eq!(stretched_interval(minute, 5, hour, 10), hour);
+    }
+
+    #[test]
+    fn pauses_token_after_secondary_rate_limit() {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_static("30"));
+        headers.insert("x-ratelimit-limit", HeaderValue::from_static("5000"));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("4000"));
+
+        record_response(
+            "PMON_TEST_RATE_LIMITED",
+            StatusCode::FORBIDDEN,
+            &headers,
+            "",
+        );
+
+        assert!(check_paused("PMON_TEST_RATE_LIMITED").is_err());
+        let rate_limit = rate_limits()
+            .into_iter()
+            .find(|rate_limit| rate_limit.token_env == "PMON_TEST_RATE_LIMITED")
+            .unwrap();
+        assert_eq!(rate_limit.remaining, Some(4000));
+        assert!(rate_limit.paused_until.is_some());
+    }
+}
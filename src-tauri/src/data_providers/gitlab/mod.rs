@@ -1,26 +1,45 @@
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
-};
-use std::thread::{self, JoinHandle};
-use std::time::Duration;
+use std::path::PathBuf;
 
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 
-use crate::core::{DataProvider, StateSummary, StateSummaryAdapter, StateSummaryGateway};
+use crate::core::config::NotificationConfig;
+use crate::core::{RepoStatus, StateSummary, StatusFetcher};
+use crate::data_providers::{http_client, ResponseCache};
 
 const DEFAULT_GITLAB_API_BASE_URL: &str = "https://gitlab.com/api/v4";
-const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(60);
 
 #[derive(Clone)]
 pub struct GitlabRepo {
     name: String,
     main_branch: String,
+    show_in_tray: bool,
+    notifications: NotificationConfig,
 }
 
 impl GitlabRepo {
     pub fn new(name: String, main_branch: String) -> Self {
-        Self { name, main_branch }
+        Self {
+            name,
+            main_branch,
+            show_in_tray: true,
+            notifications: NotificationConfig::default(),
+        }
+    }
+
+    pub fn with_show_in_tray(mut self, show_in_tray: bool) -> Self {
+        self.show_in_tray = show_in_tray;
+        self
+    }
+
+    pub fn with_notifications(mut self, notifications: NotificationConfig) -> Self {
+        self.notifications = notifications;
+        self
+    }
+
+    fn reports(&self, status: &RepoStatus) -> bool {
+        status.provider == "gitlab" && status.repo == self.name && status.branch == self.main_branch
     }
 }
 
@@ -28,9 +47,9 @@ pub struct GitlabProvider {
     token_env: String,
     repos: Vec<GitlabRepo>,
     api_base_url: String,
-    poll_interval: Duration,
-    stop_signal: Arc<AtomicBool>,
-    thread_handle: Option<JoinHandle<()>>,
+    ca_cert: Option<PathBuf>,
+    insecure_skip_verify: bool,
+    cache: ResponseCache,
 }
 
 impl GitlabProvider {
@@ -47,139 +66,159 @@ impl GitlabProvider {
             token_env,
             repos,
             api_base_url,
-            poll_interval: DEFAULT_POLL_INTERVAL,
-            stop_signal: Arc::new(AtomicBool::new(false)),
-            thread_handle: None,
+            ca_cert: None,
+            insecure_skip_verify: false,
+            cache: ResponseCache::new(),
         }
     }
 
-    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
-        self.poll_interval = poll_interval;
+    pub fn with_ca_cert(mut self, ca_cert: Option<PathBuf>) -> Self {
+        self.ca_cert = ca_cert;
         self
     }
 
-    fn poll_once(&self, client: &reqwest::blocking::Client, gateway: &StateSummaryGateway) {
-        let mut combined: Option<StateSummary> = None;
-
-        for repo in &self.repos {
-            eprintln!(
-                "GitLab provider polling {} on {}",
-                repo.name, repo.main_branch
-            );
-            match fetch_pipelines(client, &self.api_base_url, &self.token_env, repo) {
-                Ok(pipelines) => {
-                    eprintln!(
-                        "GitLab provider received {} pipelines for {}",
-                        pipelines.len(),
-                        repo.name
-                    );
-                    if let Some(summary) = state_from_pipelines(&pipelines) {
-                        eprintln!("GitLab provider summary for {} is {:?}", repo.name, summary);
-                        combined = Some(combine_summaries(combined, summary));
-                    }
-                }
-                Err(error) => {
-                    eprintln!("GitLab provider error for {}: {}", repo.name, error);
-                }
-            }
-        }
-
-        if let Some(summary) = combined {
-            gateway.set_state_summary(summary);
-        }
+    pub fn with_insecure_skip_verify(mut self, insecure_skip_verify: bool) -> Self {
+        self.insecure_skip_verify = insecure_skip_verify;
+        self
     }
 }
 
-impl DataProvider for GitlabProvider {
-    fn refresh(&mut self) {
-        let client = reqwest::blocking::Client::new();
-        let gateway = StateSummaryGateway::new();
-        self.poll_once(&client, &gateway);
-    }
+impl StatusFetcher for GitlabProvider {
+    type Repo = GitlabRepo;
+    /// Building the client fails for an unusable `ca_cert`, which is reported as a fetch error.
+    type Client = Result<reqwest::blocking::Client, String>;
 
-    fn start(&mut self, state_summary_gateway: Arc<StateSummaryGateway>) {
-        eprintln!("Starting GitLab provider");
-        if self.thread_handle.is_some() {
-            return;
-        }
+    fn name(&self) -> &'static str {
+        "GitLab"
+    }
 
-        let repos = self.repos.clone();
-        let api_base_url = self.api_base_url.clone();
-        let token_env = self.token_env.clone();
-        let poll_interval = self.poll_interval;
-        let stop_signal = self.stop_signal.clone();
+    fn repos(&self) -> &[GitlabRepo] {
+        &self.repos
+    }
 
-        self.thread_handle = Some(thread::spawn(move || {
-            let client = reqwest::blocking::Client::new();
+    fn describe(&self, repo: &GitlabRepo) -> String {
+        format!("{} on {}", repo.name, repo.main_branch)
+    }
 
-            loop {
-                if stop_signal.load(Ordering::Relaxed) {
-                    break;
-                }
+    fn client(&self) -> Self::Client {
+        http_client(self.ca_cert.as_deref(), self.insecure_skip_verify)
+    }
 
-                let mut combined: Option<StateSummary> = None;
-                for repo in &repos {
-                    eprintln!(
-                        "GitLab provider polling {} on {}",
-                        repo.name, repo.main_branch
-                    );
-                    match fetch_pipelines(&client, &api_base_url, &token_env, repo) {
-                        Ok(pipelines) => {
-                            eprintln!(
-                                "GitLab provider received {} pipelines for {}",
-                                pipelines.len(),
-                                repo.name
-                            );
-                            if let Some(summary) = state_from_pipelines(&pipelines) {
-                                eprintln!(
-                                    "GitLab provider summary for {} is {:?}",
-                                    repo.name, summary
-                                );
-                                combined = Some(combine_summaries(combined, summary));
-                            }
-                        }
-                        Err(error) => {
-                            eprintln!("GitLab provider error for {}: {}", repo.name, error);
-                        }
-                    }
+    fn fetch(&self, client: &Self::Client, repo: &GitlabRepo) -> Result<Vec<RepoStatus>, String> {
+        let client = client.as_ref().map_err(Clone::clone)?;
+        let pipelines = fetch_pipelines(
+            client,
+            &self.cache,
+            &self.api_base_url,
+            &self.token_env,
+            repo,
+        )?;
+        let Some(mut status) = repo_status_from_pipelines(repo, &pipelines) else {
+            return Ok(Vec::new());
+        };
+        if let Some(pipeline_id) = pipelines
+            .first()
+            .filter(|latest| latest.status != "success")
+            .and_then(|latest| latest.id)
+        {
+            match fetch_failed_jobs(
+                client,
+                &self.cache,
+                &self.api_base_url,
+                &self.token_env,
+                repo,
+                pipeline_id,
+            ) {
+                Ok(jobs) => add_failed_jobs(&mut status, &jobs),
+                Err(error) => {
+                    eprintln!("GitLab provider error for {} jobs: {}", repo.name, error);
                 }
+            }
+        }
+        Ok(vec![status])
+    }
 
-                if let Some(summary) = combined {
-                    eprintln!("GitLab provider combined summary is {:?}", summary);
-                    state_summary_gateway.set_state_summary(summary);
-                }
+    fn reports(&self, repo: &GitlabRepo, status: &RepoStatus) -> bool {
+        repo.reports(status)
+    }
 
-                sleep_with_stop(&stop_signal, poll_interval);
-            }
-        }));
+    fn error_status(&self, repo: &GitlabRepo) -> RepoStatus {
+        let mut status =
+            RepoStatus::new("gitlab", &repo.name, &repo.main_branch, StateSummary::Error);
+        status.show_in_tray = repo.show_in_tray;
+        status.notifications = repo.notifications.clone();
+        status
     }
 
-    fn stop(&mut self) {
-        self.stop_signal.store(true, Ordering::Relaxed);
-        if let Some(handle) = self.thread_handle.take() {
-            let _ = handle.join();
-        }
+    fn host(&self) -> Option<&str> {
+        Some(&self.api_base_url)
     }
 }
 
+#[derive(Debug, Deserialize)]
+struct Job {
+    name: String,
+    stage: String,
+    web_url: Option<String>,
+    #[serde(default)]
+    allow_failure: bool,
+}
+
 #[derive(Debug, Deserialize)]
 struct Pipeline {
+    id: Option<u64>,
     status: String,
+    web_url: Option<String>,
+    created_at: Option<String>,
+    updated_at: Option<String>,
 }
 
 fn fetch_pipelines(
     client: &reqwest::blocking::Client,
+    cache: &ResponseCache,
     api_base_url: &str,
     token_env: &str,
     repo: &GitlabRepo,
 ) -> Result<Vec<Pipeline>, String> {
-    let token = std::env::var(token_env).map_err(|_| format!("Missing env var {}", token_env))?;
     let repo_encoded = urlencoding::encode(&repo.name);
     let branch_encoded = urlencoding::encode(&repo.main_branch);
     let url = format!(
         "{}/projects/{}/pipelines?ref={}&per_page=2",
         api_base_url, repo_encoded, branch_encoded
     );
+    gitlab_get(client, cache, &url, &url, token_env)
+}
+
+fn fetch_failed_jobs(
+    client: &reqwest::blocking::Client,
+    cache: &ResponseCache,
+    api_base_url: &str,
+    token_env: &str,
+    repo: &GitlabRepo,
+    pipeline_id: u64,
+) -> Result<Vec<Job>, String> {
+    let url = format!(
+        "{}/projects/{}/pipelines/{}/jobs?scope[]=failed&per_page=100",
+        api_base_url,
+        urlencoding::encode(&repo.name),
+        pipeline_id
+    );
+    // One slot per monitored branch, the jobs of an older pipeline are not requested again.
+    let cache_slot = format!("{} {} failed jobs", repo.name, repo.main_branch);
+    gitlab_get(client, cache, &cache_slot, &url, token_env)
+}
+
+/// Sends an authenticated GET request to the GitLab API and deserializes the response.
+///
+/// The response is cached in `cache_slot`, see [`ResponseCache`].
+fn gitlab_get<T: DeserializeOwned>(
+    client: &reqwest::blocking::Client,
+    cache: &ResponseCache,
+    cache_slot: &str,
+    url: &str,
+    token_env: &str,
+) -> Result<T, String> {
+    let token = std::env::var(token_env).map_err(|_| format!("Missing env var {}", token_env))?;
 
     if gitlab_debug_enabled() {
         eprintln!(
@@ -188,9 +227,9 @@ fn fetch_pipelines(
         );
     }
 
-    let response = client
-        .get(url)
-        .header("PRIVATE-TOKEN", token)
+    let request = client.get(url).header("PRIVATE-TOKEN", token);
+    let response = cache
+        .conditional(cache_slot, url, request)
         .send()
         .map_err(|error| error.to_string())?;
 
@@ -201,6 +240,7 @@ fn fetch_pipelines(
         .and_then(|value| value.to_str().ok())
         .unwrap_or("unknown")
         .to_string();
+    let headers = response.headers().clone();
     let body = response.text().map_err(|error| error.to_string())?;
 
     if gitlab_debug_enabled() {
@@ -211,14 +251,31 @@ fn fetch_pipelines(
         eprintln!("GitLab provider response body: {}", body);
     }
 
-    if !status.is_success() {
+    if !status.is_success() && status != StatusCode::NOT_MODIFIED {
         return Err(format!(
             "GitLab API returned {} (request_id {})",
             status, request_id
         ));
     }
 
-    serde_json::from_str::<Vec<Pipeline>>(&body).map_err(|error| error.to_string())
+    let body = cache.resolve(cache_slot, url, status, &headers, body)?;
+    serde_json::from_str::<T>(&body).map_err(|error| error.to_string())
+}
+
+/// Names the failed jobs in the status and links to the first one.
+fn add_failed_jobs(status: &mut RepoStatus, jobs: &[Job]) {
+    let failed: Vec<&Job> = jobs.iter().filter(|job| !job.allow_failure).collect();
+    let Some(first) = failed.first() else {
+        return;
+    };
+    let names: Vec<String> = failed
+        .iter()
+        .map(|job| format!("{} ({})", job.name, job.stage))
+        .collect();
+    status.detail = Some(format!("{} failed", names.join(", ")));
+    if first.web_url.is_some() {
+        status.url = first.web_url.clone();
+    }
 }
 
 fn gitlab_debug_enabled() -> bool {
@@ -228,6 +285,19 @@ fn gitlab_debug_enabled() -> bool {
     )
 }
 
+fn repo_status_from_pipelines(repo: &GitlabRepo, pipelines: &[Pipeline]) -> Option<RepoStatus> {
+    let state = state_from_pipelines(pipelines)?;
+    let latest = pipelines.first()?;
+    let mut status = RepoStatus::new("gitlab", &repo.name, &repo.main_branch, state);
+    status.show_in_tray = repo.show_in_tray;
+    status.notifications = repo.notifications.clone();
+    status.run_id = latest.id;
+    status.url = latest.web_url.clone();
+    status.started_at = latest.created_at.clone();
+    status.updated_at = latest.updated_at.clone();
+    Some(status)
+}
+
 fn state_from_pipelines(pipelines: &[Pipeline]) -> Option<StateSummary> {
     let latest = pipelines.first()?;
     match latest.status.as_str() {
@@ -251,50 +321,17 @@ fn pending_state_from_history(pipelines: &[Pipeline]) -> StateSummary {
     StateSummary::OkPending
 }
 
-fn combine_summaries(current: Option<StateSummary>, next: StateSummary) -> StateSummary {
-    match current {
-        None => next,
-        Some(existing) => {
-            if summary_rank(next) > summary_rank(existing) {
-                next
-            } else {
-                existing
-            }
-        }
-    }
-}
-
-fn summary_rank(summary: StateSummary) -> u8 {
-    match summary {
-        StateSummary::Ok => 0,
-        StateSummary::OkPending => 1,
-        StateSummary::FailurePending => 2,
-        StateSummary::Failure => 3,
-    }
-}
-
-fn sleep_with_stop(stop_signal: &AtomicBool, duration: Duration) {
-    let mut remaining = duration;
-    let tick = Duration::from_secs(1);
-    while remaining > Duration::ZERO {
-        if stop_signal.load(Ordering::Relaxed) {
-            break;
-        }
-        let sleep_for = if remaining < tick { remaining } else { tick };
-        thread::sleep(sleep_for);
-        remaining = remaining.saturating_sub(sleep_for);
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use std::sync::{Arc, Mutex};
-    use std::time::Duration;
 
-    use mockito::Server;
+    use mockito::{Mock, Server};
 
     use super::{GitlabProvider, GitlabRepo};
-    use crate::core::{StateSummary, StateSummaryAdapter, StateSummaryGateway};
+    use crate::core::config::ProviderConfig;
+    use crate::core::{Poller, RepoStatus, StateSummary, StateSummaryAdapter, StateSummaryGateway};
+    use crate::data_providers::provider_from_config;
+    use crate::data_providers::test_support::{mock_json, set_token};
 
     struct TestSummaryAdapter {
         latest: Arc<Mutex<Option<StateSummary>>>,
@@ -329,8 +366,7 @@ mod tests {
 
         let repo = GitlabRepo::new("org/repo".to_string(), "main".to_string());
         let provider =
-            GitlabProvider::new_with_base_url("GITLAB_TOKEN".to_string(), vec![repo], server.url())
-                .with_poll_interval(Duration::from_secs(1));
+            GitlabProvider::new_with_base_url("GITLAB_TOKEN".to_string(), vec![repo], server.url());
 
         let gateway = StateSummaryGateway::new();
         let latest = Arc::new(Mutex::new(None));
@@ -338,7 +374,7 @@ mod tests {
             latest: latest.clone(),
         }));
 
-        provider.poll_once(&reqwest::blocking::Client::new(), &gateway);
+        Poller::new(provider).poll_once(&Ok(reqwest::blocking::Client::new()), &gateway);
 
         assert_eq!(*latest.lock().unwrap(), Some(StateSummary::Failure));
     }
@@ -372,8 +408,158 @@ mod tests {
             latest: latest.clone(),
         }));
 
-        provider.poll_once(&reqwest::blocking::Client::new(), &gateway);
+        Poller::new(provider).poll_once(&Ok(reqwest::blocking::Client::new()), &gateway);
 
         assert_eq!(*latest.lock().unwrap(), Some(StateSummary::OkPending));
     }
+
+    #[test]
+    fn names_failed_jobs_of_latest_pipeline() {
+        let mut server = Server::new();
+        unsafe {
+            std::env::set_var("PMON_TEST_GITLAB_JOBS_TOKEN", "token");
+        }
+
+        let _pipelines = server
+            .mock("GET", "/projects/org%2Frepo/pipelines")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body(
+                r#"[{"id":42,"status":"failed","web_url":"https://gitlab.example.com/p/42"}]"#,
+            )
+            .create();
+        let _jobs = server
+            .mock("GET", "/projects/org%2Frepo/pipelines/42/jobs")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "scope[]".into(),
+                "failed".into(),
+            ))
+            .with_status(200)
+            .with_body(
+                r#"[
+                    {"name":"lint","stage":"test","web_url":"https://gitlab.example.com/j/7"},
+                    {"name":"flaky","stage":"test","allow_failure":true}
+                ]"#,
+            )
+            .create();
+
+        let repo = GitlabRepo::new("org/repo".to_string(), "main".to_string());
+        let provider = GitlabProvider::new_with_base_url(
+            "PMON_TEST_GITLAB_JOBS_TOKEN".to_string(),
+            vec![repo],
+            server.url(),
+        );
+
+        let gateway = StateSummaryGateway::new();
+        Poller::new(provider).poll_once(&Ok(reqwest::blocking::Client::new()), &gateway);
+
+        let statuses = gateway.repo_statuses();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].state, StateSummary::Failure);
+        assert_eq!(statuses[0].detail.as_deref(), Some("lint (test) failed"));
+        assert_eq!(
+            statuses[0].url.as_deref(),
+            Some("https://gitlab.example.com/j/7")
+        );
+    }
+
+    /// Answers the first GET of `path` with `body` and an ETag, and the revalidation with 304.
+    fn mock_not_modified(server: &mut Server, path: &str, body: &str) -> (Mock, Mock) {
+        let first = server
+            .mock("GET", path)
+            .match_query(mockito::Matcher::Any)
+            .match_header("if-none-match", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_header("etag", "\"v1\"")
+            .with_body(body)
+            .expect(1)
+            .create();
+        let second = server
+            .mock("GET", path)
+            .match_query(mockito::Matcher::Any)
+            .match_header("if-none-match", "\"v1\"")
+            .with_status(304)
+            .expect(1)
+            .create();
+        (first, second)
+    }
+
+    #[test]
+    fn reuses_cached_pipelines_and_jobs_when_not_modified() {
+        let mut server = Server::new();
+        set_token("PMON_TEST_GITLAB_ETAG_TOKEN", "token");
+        let pipelines = mock_not_modified(
+            &mut server,
+            "/projects/org%2Frepo/pipelines",
+            r#"[{"id":42,"status":"failed","web_url":"https://gitlab.example.com/p/42"}]"#,
+        );
+        let jobs = mock_not_modified(
+            &mut server,
+            "/projects/org%2Frepo/pipelines/42/jobs",
+            r#"[{"name":"lint","stage":"test","web_url":"https://gitlab.example.com/j/7"}]"#,
+        );
+
+        let repo = GitlabRepo::new("org/repo".to_string(), "main".to_string());
+        let provider = Poller::new(GitlabProvider::new_with_base_url(
+            "PMON_TEST_GITLAB_ETAG_TOKEN".to_string(),
+            vec![repo],
+            server.url(),
+        ));
+        let client = Ok(reqwest::blocking::Client::new());
+
+        provider.poll_once(&client, &StateSummaryGateway::new());
+        let gateway = StateSummaryGateway::new();
+        provider.poll_once(&client, &gateway);
+
+        for mock in [pipelines.0, pipelines.1, jobs.0, jobs.1] {
+            mock.assert();
+        }
+        let statuses = gateway.repo_statuses();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].state, StateSummary::Failure);
+        assert_eq!(statuses[0].detail.as_deref(), Some("lint (test) failed"));
+    }
+
+    /// Builds the provider from a config entry with `extra` settings and polls it once.
+    fn poll_configured(extra: &str) -> Vec<RepoStatus> {
+        set_token("GITLAB_CONFIG_TOKEN", "test-token");
+        let config: ProviderConfig = serde_yaml::from_str(&format!(
+            "type: gitlab\ntoken:\n  env: GITLAB_CONFIG_TOKEN\nrepos:\n  - name: org/repo\n    main_branch: main\n{}",
+            extra
+        ))
+        .unwrap();
+        let gateway = StateSummaryGateway::new();
+        provider_from_config(&config).poll_now(&gateway);
+        gateway.repo_statuses()
+    }
+
+    #[test]
+    fn uses_the_api_of_the_configured_instance() {
+        let mut server = Server::new();
+        let pipelines = mock_json(
+            &mut server,
+            "/api/v4/projects/org%2Frepo/pipelines",
+            r#"[{"id":3,"status":"success","web_url":"https://gitlab.example.com/p/3"}]"#,
+        );
+
+        let statuses = poll_configured(&format!("url: {}/\n", server.url()));
+
+        pipelines.assert();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].state, StateSummary::Ok);
+    }
+
+    #[test]
+    fn reports_an_unreadable_ca_cert_as_fetch_error() {
+        let path = std::env::temp_dir().join(format!("pmon-missing-ca-{}.pem", std::process::id()));
+
+        let statuses = poll_configured(&format!("ca_cert: {}\n", path.display()));
+
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].state, StateSummary::Error);
+        assert!(statuses[0]
+            .detail
+            .as_deref()
+            .is_some_and(|detail| detail.starts_with("Cannot read")));
+    }
 }
@@ -0,0 +1,251 @@
+use std::collections::BTreeMap;
+
+use crate::core::config::MappedState;
+use crate::core::{RepoStatus, StateSummary, StatusFetcher};
+use crate::data_providers::RepoOptions;
+
+#[derive(Clone)]
+pub struct HttpEndpoint {
+    name: String,
+    url: String,
+    /// Header names and values, values may contain `${ENV_VAR}` placeholders.
+    headers: Vec<(String, String)>,
+    /// JSON Pointer or simple JSONPath to the status value.
+    status: String,
+    states: BTreeMap<String, MappedState>,
+    link: Option<String>,
+    options: RepoOptions,
+}
+
+impl HttpEndpoint {
+    /// `status` is a JSON Pointer like `/build/state` or a simple JSONPath like `$.build.state`.
+    pub fn new(
+        name: String,
+        url: String,
+        status: &str,
+        states: BTreeMap<String, MappedState>,
+    ) -> Self {
+        Self {
+            name,
+            url,
+            headers: Vec::new(),
+            status: status.to_string(),
+            states,
+            link: None,
+            options: RepoOptions::default(),
+        }
+    }
+
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    pub fn with_link(mut self, link: Option<String>) -> Self {
+        self.link = link;
+        self
+    }
+
+    pub fn with_options(mut self, options: RepoOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    fn reports(&self, status: &RepoStatus) -> bool {
+        status.provider == "http" && status.repo == self.name
+    }
+}
+
+pub struct HttpProvider {
+    endpoints: Vec<HttpEndpoint>,
+}
+
+impl HttpProvider {
+    pub fn new(endpoints: Vec<HttpEndpoint>) -> Self {
+        Self { endpoints }
+    }
+}
+
+impl StatusFetcher for HttpProvider {
+    type Repo = HttpEndpoint;
+    type Client = reqwest::blocking::Client;
+
+    fn name(&self) -> &'static str {
+        "HTTP"
+    }
+
+    fn repos(&self) -> &[HttpEndpoint] {
+        &self.endpoints
+    }
+
+    fn describe(&self, endpoint: &HttpEndpoint) -> String {
+        endpoint.name.clone()
+    }
+
+    fn client(&self) -> reqwest::blocking::Client {
+        reqwest::blocking::Client::new()
+    }
+
+    fn fetch(
+        &self,
+        client: &reqwest::blocking::Client,
+        endpoint: &HttpEndpoint,
+    ) -> Result<Vec<RepoStatus>, String> {
+        let state = fetch_state(client, endpoint)?;
+        let mut status = RepoStatus::new("http", &endpoint.name, "", state);
+        endpoint.options.apply(&mut status);
+        status.url = endpoint.link.clone();
+        Ok(vec![status])
+    }
+
+    fn reports(&self, endpoint: &HttpEndpoint, status: &RepoStatus) -> bool {
+        endpoint.reports(status)
+    }
+
+    fn error_status(&self, endpoint: &HttpEndpoint) -> RepoStatus {
+        let mut status = RepoStatus::new("http", &endpoint.name, "", StateSummary::Error);
+        endpoint.options.apply(&mut status);
+        status.url = endpoint.link.clone();
+        status
+    }
+}
+
+fn fetch_state(
+    client: &reqwest::blocking::Client,
+    endpoint: &HttpEndpoint,
+) -> Result<StateSummary, String> {
+    let mut request = client.get(&endpoint.url).header("User-Agent", "pmon");
+    for (name, value) in &endpoint.headers {
+        request = request.header(name, expand_env(value)?);
+    }
+    let response = request.send().map_err(|error| error.to_string())?;
+
+    let status = response.status();
+    let body = response.text().map_err(|error| error.to_string())?;
+
+    log::info!("HTTP provider request completed: status {}", status);
+    log::debug!("HTTP provider response: {}", body);
+
+    if !status.is_success() {
+        return Err(format!("{} returned {}", endpoint.url, status));
+    }
+
+    let pointer = json_pointer(&endpoint.status)?;
+    let json =
+        serde_json::from_str::<serde_json::Value>(&body).map_err(|error| error.to_string())?;
+    let value = match json.pointer(&pointer) {
+        Some(serde_json::Value::String(value)) => value.clone(),
+        Some(serde_json::Value::Null) | None => {
+            return Err(format!("No status at {}", endpoint.status));
+        }
+        Some(value) => value.to_string(),
+    };
+    endpoint
+        .states
+        .get(&value)
+        .map(|state| StateSummary::from(*state))
+        .ok_or_else(|| format!("Status \"{}\" has no mapping in states", value))
+}
+
+/// Converts a status expression into a JSON Pointer.
+///
+/// Expressions starting with `/` already are JSON Pointers. Expressions starting with `$` are
+/// simple JSONPaths made of `.key`, `['key']` and `[index]` steps.
+pub fn json_pointer(expression: &str) -> Result<String, String> {
+    if expression.is_empty() || expression.starts_with('/') {
+        return Ok(expression.to_string());
+    }
+    let invalid = || format!("Unsupported status expression: {}", expression);
+    let mut rest = expression.strip_prefix('$').ok_or_else(invalid)?;
+    let mut pointer = String::new();
+    while !rest.is_empty() {
+        let (step, remaining) = if let Some(after_dot) = rest.strip_prefix('.') {
+            let end = after_dot.find(['.', '[']).unwrap_or(after_dot.len());
+            after_dot.split_at(end)
+        } else if let Some(after_bracket) = rest.strip_prefix('[') {
+            let end = after_bracket.find(']').ok_or_else(invalid)?;
+            let step = after_bracket[..end].trim_matches(['\'', '"']);
+            (step, &after_bracket[end + 1..])
+        } else {
+            return Err(invalid());
+        };
+        if step.is_empty() {
+            return Err(invalid());
+        }
+        pointer.push('/');
+        pointer.push_str(&step.replace('~', "~0").replace('/', "~1"));
+        rest = remaining;
+    }
+    Ok(pointer)
+}
+
+/// Replaces `${NAME}` placeholders with the value of the environment variable `NAME`.
+pub fn expand_env(value: &str) -> Result<String, String> {
+    let mut expanded = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| format!("Unclosed placeholder in \"{}\"", value))?;
+        let name = &rest[start + 2..start + end];
+        let env_value = std::env::var(name).map_err(|_| format!("Missing env var {}", name))?;
+        expanded.push_str(&rest[..start]);
+        expanded.push_str(&env_value);
+        rest = &rest[start + end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use mockito::Server;
+
+    use super::{json_pointer, HttpEndpoint, HttpProvider};
+    use crate::core::config::MappedState;
+    use crate::core::StateSummary;
+    use crate::data_providers::test_support::{poll_once, set_token};
+
+    #[test]
+    fn maps_extracted_status_to_state() {
+        let mut server = Server::new();
+        set_token("PMON_TEST_STATUS_TOKEN", "secret");
+
+        let _mock = server
+            .mock("GET", "/status")
+            .match_header("authorization", "Bearer secret")
+            .with_status(200)
+            .with_body(r#"{"builds":[{"state":"broken"}]}"#)
+            .create();
+
+        let states = BTreeMap::from([
+            ("green".to_string(), MappedState::Ok),
+            ("broken".to_string(), MappedState::Failure),
+        ]);
+        let endpoint = HttpEndpoint::new(
+            "deployments".to_string(),
+            format!("{}/status", server.url()),
+            "$.builds[0].state",
+            states,
+        )
+        .with_headers(vec![(
+            "Authorization".to_string(),
+            "Bearer ${PMON_TEST_STATUS_TOKEN}".to_string(),
+        )]);
+        let statuses = poll_once(HttpProvider::new(vec![endpoint]));
+
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].repo, "deployments");
+        assert_eq!(statuses[0].state, StateSummary::Failure);
+    }
+
+    #[test]
+    fn converts_json_path_to_pointer() {
+        assert_eq!(json_pointer("/a/0").unwrap(), "/a/0");
+        assert_eq!(json_pointer("$.a.b").unwrap(), "/a/b");
+        assert_eq!(json_pointer("$.items[2]['x/y']").unwrap(), "/items/2/x~1y");
+        assert!(json_pointer("a.b").is_err());
+    }
+}
@@ -0,0 +1,307 @@
+use chrono::{DateTime, SecondsFormat};
+use serde::Deserialize;
+
+use crate::core::{RepoStatus, StateSummary, StatusFetcher};
+use crate::data_providers::RepoOptions;
+
+const JOB_TREE: &str =
+    "lastBuild[number,result,building,url,timestamp,duration],lastCompletedBuild[result]";
+
+#[derive(Clone)]
+pub struct JenkinsJob {
+    /// Path of the job, with folders and multibranch projects separated by `/`.
+    name: String,
+    /// Branch of a multibranch project, appended to the path as the branch job.
+    branch: Option<String>,
+    options: RepoOptions,
+}
+
+impl JenkinsJob {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            branch: None,
+            options: RepoOptions::default(),
+        }
+    }
+
+    pub fn with_branch(mut self, branch: Option<String>) -> Self {
+        self.branch = branch;
+        self
+    }
+
+    pub fn with_options(mut self, options: RepoOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    fn branch_name(&self) -> &str {
+        self.branch.as_deref().unwrap_or_default()
+    }
+
+    fn reports(&self, status: &RepoStatus) -> bool {
+        status.provider == "jenkins"
+            && status.repo == self.name
+            && status.branch == self.branch_name()
+    }
+
+    /// The URL path of the job, e.g. `/job/folder/job/project/job/main`.
+    fn url_path(&self) -> String {
+        // Jenkins names branch jobs after the encoded branch name, which is encoded again in URLs.
+        let branch_job = self
+            .branch
+            .as_deref()
+            .map(|branch| urlencoding::encode(branch).into_owned());
+        self.name
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(str::to_string)
+            .chain(branch_job)
+            .map(|segment| format!("/job/{}", urlencoding::encode(&segment)))
+            .collect()
+    }
+}
+
+/// Environment variables holding the user name and API token for basic auth.
+#[derive(Clone)]
+pub struct JenkinsCredentials {
+    pub user_env: String,
+    pub token_env: String,
+}
+
+pub struct JenkinsProvider {
+    credentials: Option<JenkinsCredentials>,
+    jobs: Vec<JenkinsJob>,
+    base_url: String,
+}
+
+impl JenkinsProvider {
+    /// `url` is the root of the Jenkins controller, e.g. `https://jenkins.example.com`.
+    pub fn new(url: &str, credentials: Option<JenkinsCredentials>, jobs: Vec<JenkinsJob>) -> Self {
+        Self {
+            credentials,
+            jobs,
+            base_url: url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl StatusFetcher for JenkinsProvider {
+    type Repo = JenkinsJob;
+    type Client = reqwest::blocking::Client;
+
+    fn name(&self) -> &'static str {
+        "Jenkins"
+    }
+
+    fn repos(&self) -> &[JenkinsJob] {
+        &self.jobs
+    }
+
+    fn describe(&self, job: &JenkinsJob) -> String {
+        match &job.branch {
+            Some(branch) => format!("{} on {}", job.name, branch),
+            None => job.name.clone(),
+        }
+    }
+
+    fn client(&self) -> reqwest::blocking::Client {
+        reqwest::blocking::Client::new()
+    }
+
+    fn fetch(
+        &self,
+        client: &reqwest::blocking::Client,
+        job: &JenkinsJob,
+    ) -> Result<Vec<RepoStatus>, String> {
+        let details = fetch_job(client, &self.base_url, self.credentials.as_ref(), job)?;
+        Ok(repo_status_from_job(job, &details).into_iter().collect())
+    }
+
+    fn reports(&self, job: &JenkinsJob, status: &RepoStatus) -> bool {
+        job.reports(status)
+    }
+
+    fn error_status(&self, job: &JenkinsJob) -> RepoStatus {
+        let mut status =
+            RepoStatus::new("jenkins", &job.name, job.branch_name(), StateSummary::Error);
+        job.options.apply(&mut status);
+        status
+    }
+
+    fn host(&self) -> Option<&str> {
+        Some(&self.base_url)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Job {
+    last_build: Option<Build>,
+    last_completed_build: Option<Build>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Build {
+    number: Option<u64>,
+    result: Option<String>,
+    #[serde(default)]
+    building: bool,
+    url: Option<String>,
+    /// Start of the build in milliseconds since the epoch.
+    timestamp: Option<i64>,
+    /// Duration of the build in milliseconds, 0 while it is running.
+    duration: Option<i64>,
+}
+
+fn fetch_job(
+    client: &reqwest::blocking::Client,
+    base_url: &str,
+    credentials: Option<&JenkinsCredentials>,
+    job: &JenkinsJob,
+) -> Result<Job, String> {
+    let url = format!(
+        "{}{}/api/json?tree={}",
+        base_url,
+        job.url_path(),
+        urlencoding::encode(JOB_TREE)
+    );
+
+    let mut request = client.get(url).header("User-Agent", "pmon");
+    if let Some(credentials) = credentials {
+        let user = std::env::var(&credentials.user_env)
+            .map_err(|_| format!("Missing env var {}", credentials.user_env))?;
+        let token = std::env::var(&credentials.token_env)
+            .map_err(|_| format!("Missing env var {}", credentials.token_env))?;
+        request = request.basic_auth(user, Some(token));
+    }
+    let response = request.send().map_err(|error| error.to_string())?;
+
+    let status = response.status();
+    let body = response.text().map_err(|error| error.to_string())?;
+
+    log::info!("Jenkins API request completed: status {}", status);
+    log::debug!("Jenkins API response: {}", body);
+
+    if !status.is_success() {
+        return Err(format!("Jenkins API returned {}", status));
+    }
+
+    serde_json::from_str::<Job>(&body).map_err(|error| error.to_string())
+}
+
+fn repo_status_from_job(job: &JenkinsJob, details: &Job) -> Option<RepoStatus> {
+    let state = state_from_job(details)?;
+    let latest = details.last_build.as_ref()?;
+    let mut status = RepoStatus::new("jenkins", &job.name, job.branch_name(), state);
+    job.options.apply(&mut status);
+    status.run_id = latest.number;
+    status.url = latest.url.clone();
+    status.started_at = latest.timestamp.and_then(format_timestamp);
+    status.updated_at = match (latest.building, latest.timestamp, latest.duration) {
+        (false, Some(timestamp), Some(duration)) => format_timestamp(timestamp + duration),
+        _ => status.started_at.clone(),
+    };
+    Some(status)
+}
+
+fn state_from_job(details: &Job) -> Option<StateSummary> {
+    let latest = details.last_build.as_ref()?;
+    if latest.building {
+        let previous = details
+            .last_completed_build
+            .as_ref()
+            .and_then(|build| result_state(build.result.as_deref()));
+        return Some(match previous {
+            Some(StateSummary::Failure) => StateSummary::FailurePending,
+            _ => StateSummary::OkPending,
+        });
+    }
+    result_state(latest.result.as_deref())
+}
+
+fn result_state(result: Option<&str>) -> Option<StateSummary> {
+    match result {
+        Some("SUCCESS") => Some(StateSummary::Ok),
+        Some("FAILURE") | Some("UNSTABLE") | Some("ABORTED") => Some(StateSummary::Failure),
+        _ => None,
+    }
+}
+
+fn format_timestamp(millis: i64) -> Option<String> {
+    DateTime::from_timestamp_millis(millis)
+        .map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::Server;
+
+    use super::{JenkinsCredentials, JenkinsJob, JenkinsProvider};
+    use crate::core::StateSummary;
+    use crate::data_providers::test_support::{mock_json, poll_once, set_token};
+
+    #[test]
+    fn reports_failure_pending_for_running_branch_job_after_failure() {
+        let mut server = Server::new();
+        set_token("JENKINS_USER", "alice");
+        set_token("JENKINS_TOKEN", "secret");
+
+        let _mock = server
+            .mock("GET", "/job/legacy/job/app/job/feature%252Flogin/api/json")
+            .match_query(mockito::Matcher::Any)
+            // "alice:secret" in base64
+            .match_header("authorization", "Basic YWxpY2U6c2VjcmV0")
+            .with_status(200)
+            .with_body(
+                r#"{"lastBuild":{"number":7,"result":null,"building":true,"url":"https://jenkins.example.com/job/legacy/job/app/job/feature%252Flogin/7/","timestamp":1735725600000,"duration":0},"lastCompletedBuild":{"result":"UNSTABLE"}}"#,
+            )
+            .create();
+
+        let job = JenkinsJob::new("legacy/app".to_string())
+            .with_branch(Some("feature/login".to_string()));
+        let credentials = JenkinsCredentials {
+            user_env: "JENKINS_USER".to_string(),
+            token_env: "JENKINS_TOKEN".to_string(),
+        };
+        let statuses = poll_once(JenkinsProvider::new(
+            &server.url(),
+            Some(credentials),
+            vec![job],
+        ));
+
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].repo, "legacy/app");
+        assert_eq!(statuses[0].branch, "feature/login");
+        assert_eq!(statuses[0].state, StateSummary::FailurePending);
+        assert_eq!(statuses[0].run_id, Some(7));
+        assert_eq!(
+            statuses[0].started_at.as_deref(),
+            Some("2025-01-01T10:00:00Z")
+        );
+    }
+
+    #[test]
+    fn reports_ok_for_successful_job_without_credentials() {
+        let mut server = Server::new();
+
+        let _mock = mock_json(
+            &mut server,
+            "/job/nightly/api/json",
+            r#"{"lastBuild":{"number":3,"result":"SUCCESS","building":false,"timestamp":1735725600000,"duration":60000},"lastCompletedBuild":{"result":"SUCCESS"}}"#,
+        );
+
+        let statuses = poll_once(JenkinsProvider::new(
+            &server.url(),
+            None,
+            vec![JenkinsJob::new("nightly".to_string())],
+        ));
+
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].state, StateSummary::Ok);
+        assert_eq!(
+            statuses[0].updated_at.as_deref(),
+            Some("2025-01-01T10:01:00Z")
+        );
+    }
+}
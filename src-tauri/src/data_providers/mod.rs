@@ -1,47 +1,238 @@
+mod command;
+mod gitea;
 mod github;
 mod gitlab;
+mod http;
+mod jenkins;
+mod repo_options;
+mod response_cache;
+#[cfg(test)]
+mod test_support;
+mod woodpecker;
 
-use crate::core::config::{Config, ProviderConfig};
-use crate::core::DataProvider;
+use std::path::Path;
+use std::time::Duration;
 
-pub use github::GithubProvider;
+use crate::core::config::{Config, ProviderConfig, WorkflowSelection};
+use crate::core::{DataProvider, Poller};
+
+pub(crate) use response_cache::ResponseCache;
+
+pub use command::CommandProvider;
+pub use gitea::GiteaProvider;
+pub use github::{rate_limits, GithubProvider, RateLimit};
 pub use gitlab::GitlabProvider;
+pub use http::{expand_env, json_pointer, HttpProvider};
+pub use jenkins::JenkinsProvider;
+pub use repo_options::RepoOptions;
+pub use woodpecker::WoodpeckerProvider;
 
 pub fn providers_from_config(config: &Config) -> Vec<Box<dyn DataProvider>> {
-    let mut providers: Vec<Box<dyn DataProvider>> = Vec::new();
+    config.providers.iter().map(provider_from_config).collect()
+}
 
-    for provider in &config.providers {
-        match provider {
-            ProviderConfig::Gitlab { token, repos } => {
-                let gitlab_repos = repos
-                    .iter()
-                    .map(|repo| {
-                        gitlab::GitlabRepo::new(repo.name.clone(), repo.main_branch.clone())
-                    })
-                    .collect();
-                providers.push(Box::new(GitlabProvider::new(
+pub fn provider_from_config(provider: &ProviderConfig) -> Box<dyn DataProvider> {
+    match provider {
+        ProviderConfig::Gitlab {
+            url,
+            token,
+            ca_cert,
+            insecure_skip_verify,
+            repos,
+        } => {
+            let gitlab_repos = repos
+                .iter()
+                .map(|repo| {
+                    gitlab::GitlabRepo::new(repo.name.clone(), repo.main_branch.clone())
+                        .with_show_in_tray(repo.show_in_tray)
+                        .with_notifications(repo.notifications.clone())
+                })
+                .collect();
+            let provider = match url {
+                Some(url) => GitlabProvider::new_with_base_url(
                     token.env.clone(),
                     gitlab_repos,
-                )));
-            }
-            ProviderConfig::Github { token, repos } => {
-                let github_repos = repos
-                    .iter()
-                    .map(|repo| {
-                        github::GithubRepo::new(
-                            repo.name.clone(),
-                            repo.main_branch.clone(),
-                            repo.workflow.clone(),
-                        )
-                    })
-                    .collect();
-                providers.push(Box::new(GithubProvider::new(
+                    format!("{}/api/v4", url.trim_end_matches('/')),
+                ),
+                None => GitlabProvider::new(token.env.clone(), gitlab_repos),
+            };
+            Box::new(Poller::new(
+                provider
+                    .with_ca_cert(ca_cert.clone())
+                    .with_insecure_skip_verify(*insecure_skip_verify),
+            ))
+        }
+        ProviderConfig::Github {
+            token,
+            api_base_url,
+            ca_cert,
+            repos,
+        } => {
+            let github_repos = repos
+                .iter()
+                .map(|repo| {
+                    let workflows = repo
+                        .workflow
+                        .as_ref()
+                        .map(WorkflowSelection::patterns)
+                        .unwrap_or_default();
+                    github::GithubRepo::new_with_workflows(
+                        repo.name.clone(),
+                        repo.main_branch.clone(),
+                        workflows,
+                    )
+                    .with_mode(repo.mode)
+                    .with_show_in_tray(repo.show_in_tray)
+                    .with_notifications(repo.notifications.clone())
+                })
+                .collect();
+            let provider = match api_base_url {
+                Some(api_base_url) => GithubProvider::new_with_base_url(
                     token.env.clone(),
                     github_repos,
-                )));
-            }
+                    api_base_url.clone(),
+                ),
+                None => GithubProvider::new(token.env.clone(), github_repos),
+            };
+            Box::new(Poller::new(provider.with_ca_cert(ca_cert.clone())))
+        }
+        ProviderConfig::Gitea { url, token, repos } => {
+            let gitea_repos = repos
+                .iter()
+                .map(|repo| {
+                    gitea::GiteaRepo::new(repo.name.clone(), repo.main_branch.clone())
+                        .with_workflow(repo.workflow.clone())
+                        .with_options(RepoOptions::new(
+                            repo.show_in_tray,
+                            repo.notifications.clone(),
+                        ))
+                })
+                .collect();
+            Box::new(Poller::new(GiteaProvider::new(
+                url,
+                token.env.clone(),
+                gitea_repos,
+            )))
+        }
+        ProviderConfig::Jenkins {
+            url,
+            user,
+            token,
+            jobs,
+        } => {
+            let credentials = match (user, token) {
+                (Some(user), Some(token)) => Some(jenkins::JenkinsCredentials {
+                    user_env: user.env.clone(),
+                    token_env: token.env.clone(),
+                }),
+                (None, None) => None,
+                _ => {
+                    log::warn!(
+                        "Jenkins {} needs both user and token, polling without credentials",
+                        url
+                    );
+                    None
+                }
+            };
+            let jenkins_jobs = jobs
+                .iter()
+                .map(|job| {
+                    jenkins::JenkinsJob::new(job.name.clone())
+                        .with_branch(job.branch.clone())
+                        .with_options(RepoOptions::new(
+                            job.show_in_tray,
+                            job.notifications.clone(),
+                        ))
+                })
+                .collect();
+            Box::new(Poller::new(JenkinsProvider::new(
+                url,
+                credentials,
+                jenkins_jobs,
+            )))
+        }
+        ProviderConfig::Woodpecker {
+            url,
+            api,
+            token,
+            repos,
+        } => {
+            let woodpecker_repos = repos
+                .iter()
+                .map(|repo| {
+                    woodpecker::WoodpeckerRepo::new(repo.name.clone(), repo.main_branch.clone())
+                        .with_options(RepoOptions::new(
+                            repo.show_in_tray,
+                            repo.notifications.clone(),
+                        ))
+                })
+                .collect();
+            Box::new(Poller::new(WoodpeckerProvider::new(
+                url,
+                *api,
+                token.env.clone(),
+                woodpecker_repos,
+            )))
+        }
+        ProviderConfig::Http { endpoints } => {
+            let http_endpoints = endpoints
+                .iter()
+                .map(|endpoint| {
+                    http::HttpEndpoint::new(
+                        endpoint.name.clone(),
+                        endpoint.url.clone(),
+                        &endpoint.status,
+                        endpoint.states.clone(),
+                    )
+                    .with_headers(
+                        endpoint
+                            .headers
+                            .iter()
+                            .map(|header| (header.name.clone(), header.value.clone()))
+                            .collect(),
+                    )
+                    .with_link(endpoint.link.clone())
+                    .with_options(RepoOptions::new(
+                        endpoint.show_in_tray,
+                        endpoint.notifications.clone(),
+                    ))
+                })
+                .collect();
+            Box::new(Poller::new(HttpProvider::new(http_endpoints)))
+        }
+        ProviderConfig::Command { commands } => {
+            let local_commands = commands
+                .iter()
+                .map(|command| {
+                    command::LocalCommand::new(command.name.clone(), command.command.clone())
+                        .with_working_dir(command.working_dir.clone())
+                        .with_env(command.env.clone())
+                        .with_timeout(Duration::from_secs(command.timeout_seconds))
+                        .with_states(command.states.clone())
+                        .with_options(RepoOptions::new(
+                            command.show_in_tray,
+                            command.notifications.clone(),
+                        ))
+                })
+                .collect();
+            Box::new(Poller::new(CommandProvider::new(local_commands)))
         }
     }
+}
 
-    providers
+/// Builds an HTTP client that additionally trusts the CA certificate in the given PEM file.
+pub(crate) fn http_client(
+    ca_cert: Option<&Path>,
+    insecure_skip_verify: bool,
+) -> Result<reqwest::blocking::Client, String> {
+    let mut builder =
+        reqwest::blocking::Client::builder().danger_accept_invalid_certs(insecure_skip_verify);
+    if let Some(ca_cert) = ca_cert {
+        let pem = std::fs::read(ca_cert)
+            .map_err(|error| format!("Cannot read {}: {}", ca_cert.display(), error))?;
+        let certificate = reqwest::Certificate::from_pem(&pem)
+            .map_err(|error| format!("Invalid certificate {}: {}", ca_cert.display(), error))?;
+        builder = builder.add_root_certificate(certificate);
+    }
+    builder.build().map_err(|error| error.to_string())
 }
@@ -0,0 +1,31 @@
+use crate::core::config::NotificationConfig;
+use crate::core::RepoStatus;
+
+/// How the statuses of a monitored repo, job or endpoint are presented, the same for every
+/// provider.
+#[derive(Clone)]
+pub struct RepoOptions {
+    show_in_tray: bool,
+    notifications: NotificationConfig,
+}
+
+impl RepoOptions {
+    pub fn new(show_in_tray: bool, notifications: NotificationConfig) -> Self {
+        Self {
+            show_in_tray,
+            notifications,
+        }
+    }
+
+    /// Copies the options onto a status published for the repo.
+    pub(crate) fn apply(&self, status: &mut RepoStatus) {
+        status.show_in_tray = self.show_in_tray;
+        status.notifications = self.notifications.clone();
+    }
+}
+
+impl Default for RepoOptions {
+    fn default() -> Self {
+        Self::new(true, NotificationConfig::default())
+    }
+}
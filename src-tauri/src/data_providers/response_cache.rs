@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use reqwest::blocking::RequestBuilder;
+use reqwest::header::{HeaderMap, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+
+struct CachedResponse {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    body: String,
+}
+
+/// Remembers the last response per slot, so unchanged resources can be requested conditionally.
+///
+/// APIs answer a conditional request with `304 Not Modified` and an empty body when nothing
+/// changed, GitHub does not count these against the rate limit.
+///
+/// A slot names what is requested independently of the URL, e.g. the check runs of the head
+/// commit of a repo. It holds the response of a single URL, so the response for a new commit
+/// replaces the previous one and the cache does not grow beyond the configured repos.
+#[derive(Default)]
+pub(crate) struct ResponseCache {
+    responses: Mutex<HashMap<String, CachedResponse>>,
+}
+
+impl ResponseCache {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `If-None-Match` and `If-Modified-Since` headers for a previously cached response.
+    pub(crate) fn conditional(
+        &self,
+        slot: &str,
+        url: &str,
+        mut request: RequestBuilder,
+    ) -> RequestBuilder {
+        let Ok(responses) = self.responses.lock() else {
+            return request;
+        };
+        if let Some(cached) = responses.get(slot).filter(|cached| cached.url == url) {
+            if let Some(etag) = &cached.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        request
+    }
+
+    /// Returns the cached body for `304 Not Modified` and caches any other successful response.
+    pub(crate) fn resolve(
+        &self,
+        slot: &str,
+        url: &str,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: String,
+    ) -> Result<String, String> {
+        if status == StatusCode::NOT_MODIFIED {
+            return self
+                .responses
+                .lock()
+                .ok()
+                .and_then(|responses| {
+                    responses
+                        .get(slot)
+                        .filter(|cached| cached.url == url)
+                        .map(|cached| cached.body.clone())
+                })
+                .ok_or_else(|| format!("{} is not modified, but not cached either", url));
+        }
+        self.store(slot, url, headers, &body);
+        Ok(body)
+    }
+
+    fn store(&self, slot: &str, url: &str, headers: &HeaderMap, body: &str) {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let etag = header(ETAG);
+        let last_modified = header(LAST_MODIFIED);
+        let Ok(mut responses) = self.responses.lock() else {
+            return;
+        };
+        if etag.is_none() && last_modified.is_none() {
+            responses.remove(slot);
+            return;
+        }
+        responses.insert(
+            slot.to_string(),
+            CachedResponse {
+                url: url.to_string(),
+                etag,
+                last_modified,
+                body: body.to_string(),
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{HeaderMap, HeaderValue, ETAG, IF_NONE_MATCH};
+    use reqwest::StatusCode;
+
+    use super::ResponseCache;
+
+    fn if_none_match(cache: &ResponseCache, slot: &str, url: &str) -> Option<HeaderValue> {
+        let request = cache.conditional(slot, url, reqwest::blocking::Client::new().get(url));
+        request
+            .build()
+            .unwrap()
+            .headers()
+            .get(IF_NONE_MATCH)
+            .cloned()
+    }
+
+    #[test]
+    fn a_new_url_replaces_the_response_of_its_slot() {
+        let cache = ResponseCache::new();
+        let mut headers = HeaderMap::new();
+        let old_url = "http://localhost/commits/aaa/status";
+        let new_url = "http://localhost/commits/bbb/status";
+        headers.insert(ETAG, HeaderValue::from_static("\"aaa\""));
+        cache
+            .resolve("head", old_url, StatusCode::OK, &headers, "{}".to_string())
+            .unwrap();
+        headers.insert(ETAG, HeaderValue::from_static("\"bbb\""));
+        cache
+            .resolve("head", new_url, StatusCode::OK, &headers, "{}".to_string())
+            .unwrap();
+
+        assert_eq!(cache.responses.lock().unwrap().len(), 1);
+        assert_eq!(if_none_match(&cache, "head", old_url), None);
+        assert_eq!(
+            if_none_match(&cache, "head", new_url),
+            Some(HeaderValue::from_static("\"bbb\""))
+        );
+        assert!(cache
+            .resolve(
+                "head",
+                old_url,
+                StatusCode::NOT_MODIFIED,
+                &headers,
+                String::new()
+            )
+            .is_err());
+    }
+}
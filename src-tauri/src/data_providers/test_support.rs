@@ -0,0 +1,31 @@
+//! Fixtures shared by the provider tests.
+
+use mockito::{Matcher, Mock, Server};
+
+use crate::core::{Poller, RepoStatus, StateSummaryGateway, StatusFetcher};
+
+/// Sets the environment variable a provider reads its token from.
+pub(crate) fn set_token(name: &str, value: &str) {
+    // Tests only set variables and each test uses its own names.
+    unsafe {
+        std::env::set_var(name, value);
+    }
+}
+
+/// Answers GET requests for `path`, with any query, with the JSON `body`.
+pub(crate) fn mock_json(server: &mut Server, path: &str, body: &str) -> Mock {
+    server
+        .mock("GET", path)
+        .match_query(Matcher::Any)
+        .with_status(200)
+        .with_body(body)
+        .create()
+}
+
+/// Polls every repo of the provider once and returns the published statuses.
+pub(crate) fn poll_once<F: StatusFetcher>(provider: F) -> Vec<RepoStatus> {
+    let client = provider.client();
+    let gateway = StateSummaryGateway::new();
+    Poller::new(provider).poll_once(&client, &gateway);
+    gateway.repo_statuses()
+}
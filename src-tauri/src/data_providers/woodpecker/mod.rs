@@ -0,0 +1,340 @@
+use chrono::{DateTime, SecondsFormat};
+use serde::Deserialize;
+
+use crate::core::config::WoodpeckerApi;
+use crate::core::{RepoStatus, StateSummary, StatusFetcher};
+use crate::data_providers::RepoOptions;
+
+// Pull request pipelines are skipped, fetch enough to find the latest two of the branch itself.
+const PIPELINES_PER_PAGE: usize = 20;
+
+#[derive(Clone)]
+pub struct WoodpeckerRepo {
+    name: String,
+    main_branch: String,
+    options: RepoOptions,
+}
+
+impl WoodpeckerRepo {
+    pub fn new(name: String, main_branch: String) -> Self {
+        Self {
+            name,
+            main_branch,
+            options: RepoOptions::default(),
+        }
+    }
+
+    pub fn with_options(mut self, options: RepoOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    fn reports(&self, status: &RepoStatus) -> bool {
+        status.provider == "woodpecker"
+            && status.repo == self.name
+            && status.branch == self.main_branch
+    }
+}
+
+pub struct WoodpeckerProvider {
+    token_env: String,
+    repos: Vec<WoodpeckerRepo>,
+    base_url: String,
+    api: WoodpeckerApi,
+}
+
+impl WoodpeckerProvider {
+    /// `url` is the root of the Woodpecker or Drone server, e.g. `https://ci.example.com`.
+    pub fn new(
+        url: &str,
+        api: WoodpeckerApi,
+        token_env: String,
+        repos: Vec<WoodpeckerRepo>,
+    ) -> Self {
+        Self {
+            token_env,
+            repos,
+            base_url: url.trim_end_matches('/').to_string(),
+            api,
+        }
+    }
+}
+
+impl StatusFetcher for WoodpeckerProvider {
+    type Repo = WoodpeckerRepo;
+    type Client = reqwest::blocking::Client;
+
+    fn name(&self) -> &'static str {
+        "Woodpecker"
+    }
+
+    fn repos(&self) -> &[WoodpeckerRepo] {
+        &self.repos
+    }
+
+    fn describe(&self, repo: &WoodpeckerRepo) -> String {
+        format!("{} on {}", repo.name, repo.main_branch)
+    }
+
+    fn client(&self) -> reqwest::blocking::Client {
+        reqwest::blocking::Client::new()
+    }
+
+    fn fetch(
+        &self,
+        client: &reqwest::blocking::Client,
+        repo: &WoodpeckerRepo,
+    ) -> Result<Vec<RepoStatus>, String> {
+        let (pipelines, web_url) =
+            fetch_pipelines(client, &self.base_url, self.api, &self.token_env, repo)?;
+        Ok(repo_status_from_pipelines(repo, &pipelines, &web_url)
+            .into_iter()
+            .collect())
+    }
+
+    fn reports(&self, repo: &WoodpeckerRepo, status: &RepoStatus) -> bool {
+        repo.reports(status)
+    }
+
+    fn error_status(&self, repo: &WoodpeckerRepo) -> RepoStatus {
+        let mut status = RepoStatus::new(
+            "woodpecker",
+            &repo.name,
+            &repo.main_branch,
+            StateSummary::Error,
+        );
+        repo.options.apply(&mut status);
+        status
+    }
+
+    fn host(&self) -> Option<&str> {
+        Some(&self.base_url)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Repo {
+    id: u64,
+}
+
+/// A Woodpecker pipeline or a Drone build, both share the fields pmon needs.
+#[derive(Debug, Deserialize)]
+struct Pipeline {
+    number: Option<u64>,
+    status: String,
+    #[serde(default)]
+    event: String,
+    #[serde(default)]
+    branch: String,
+    /// Unix timestamps in seconds, 0 if not reached yet.
+    #[serde(default)]
+    started: i64,
+    #[serde(default)]
+    finished: i64,
+}
+
+/// Returns the pipelines of the main branch, newest first, and the web URL of the repo.
+fn fetch_pipelines(
+    client: &reqwest::blocking::Client,
+    base_url: &str,
+    api: WoodpeckerApi,
+    token_env: &str,
+    repo: &WoodpeckerRepo,
+) -> Result<(Vec<Pipeline>, String), String> {
+    let token = std::env::var(token_env).map_err(|_| format!("Missing env var {}", token_env))?;
+    let (owner, repo_name) = split_repo_name(&repo.name)?;
+    let branch = urlencoding::encode(&repo.main_branch);
+    let (url, web_url) = match api {
+        WoodpeckerApi::Woodpecker => {
+            // Woodpecker 2 addresses repos by their id, which has to be looked up first.
+            let lookup_url = format!("{}/api/repos/lookup/{}/{}", base_url, owner, repo_name);
+            let body = get(client, &lookup_url, &token)?;
+            let repo = serde_json::from_str::<Repo>(&body).map_err(|error| error.to_string())?;
+            (
+                format!(
+                    "{}/api/repos/{}/pipelines?branch={}&per_page={}",
+                    base_url, repo.id, branch, PIPELINES_PER_PAGE
+                ),
+                format!("{}/repos/{}/pipeline", base_url, repo.id),
+            )
+        }
+        WoodpeckerApi::Drone => (
+            format!(
+                "{}/api/repos/{}/{}/builds?branch={}&per_page={}",
+                base_url, owner, repo_name, branch, PIPELINES_PER_PAGE
+            ),
+            format!("{}/{}/{}", base_url, owner, repo_name),
+        ),
+    };
+
+    let body = get(client, &url, &token)?;
+    let pipelines =
+        serde_json::from_str::<Vec<Pipeline>>(&body).map_err(|error| error.to_string())?;
+    let pipelines = pipelines
+        .into_iter()
+        .filter(|pipeline| pipeline.branch == repo.main_branch && pipeline.event != "pull_request")
+        .collect();
+    Ok((pipelines, web_url))
+}
+
+fn get(client: &reqwest::blocking::Client, url: &str, token: &str) -> Result<String, String> {
+    let response = client
+        .get(url)
+        .header("Authorization", format!("Bearer {}", token))
+        .header("User-Agent", "pmon")
+        .send()
+        .map_err(|error| error.to_string())?;
+
+    let status = response.status();
+    let body = response.text().map_err(|error| error.to_string())?;
+
+    log::info!("Woodpecker API request completed: status {}", status);
+    log::debug!("Woodpecker API response: {}", body);
+
+    if !status.is_success() {
+        return Err(format!("Woodpecker API returned {}", status));
+    }
+    Ok(body)
+}
+
+fn split_repo_name(name: &str) -> Result<(String, String), String> {
+    let (owner, repo) = name
+        .split_once('/')
+        .ok_or_else(|| format!("Invalid Woodpecker repo name: {}", name))?;
+    Ok((
+        urlencoding::encode(owner).into_owned(),
+        urlencoding::encode(repo).into_owned(),
+    ))
+}
+
+fn repo_status_from_pipelines(
+    repo: &WoodpeckerRepo,
+    pipelines: &[Pipeline],
+    web_url: &str,
+) -> Option<RepoStatus> {
+    let state = state_from_pipelines(pipelines)?;
+    let latest = pipelines.first()?;
+    let mut status = RepoStatus::new("woodpecker", &repo.name, &repo.main_branch, state);
+    repo.options.apply(&mut status);
+    status.run_id = latest.number;
+    status.url = latest
+        .number
+        .map(|number| format!("{}/{}", web_url, number));
+    status.started_at = format_timestamp(latest.started);
+    status.updated_at = format_timestamp(latest.finished).or_else(|| status.started_at.clone());
+    Some(status)
+}
+
+fn state_from_pipelines(pipelines: &[Pipeline]) -> Option<StateSummary> {
+    let latest = pipelines.first()?;
+
+    match latest.status.as_str() {
+        "pending" | "running" | "blocked" => Some(pending_state_from_history(pipelines)),
+        status => result_state(status),
+    }
+}
+
+fn result_state(status: &str) -> Option<StateSummary> {
+    match status {
+        "success" => Some(StateSummary::Ok),
+        "failure" | "killed" | "error" | "declined" => Some(StateSummary::Failure),
+        _ => None,
+    }
+}
+
+fn pending_state_from_history(pipelines: &[Pipeline]) -> StateSummary {
+    for pipeline in pipelines.iter().skip(1) {
+        match result_state(&pipeline.status) {
+            Some(StateSummary::Failure) => return StateSummary::FailurePending,
+            Some(StateSummary::Ok) => return StateSummary::OkPending,
+            _ => {}
+        }
+    }
+
+    StateSummary::OkPending
+}
+
+fn format_timestamp(seconds: i64) -> Option<String> {
+    if seconds <= 0 {
+        return None;
+    }
+    DateTime::from_timestamp(seconds, 0).map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::Server;
+
+    use super::{WoodpeckerProvider, WoodpeckerRepo};
+    use crate::core::config::WoodpeckerApi;
+    use crate::core::StateSummary;
+    use crate::data_providers::test_support::{mock_json, poll_once, set_token};
+
+    fn provider(server: &Server, api: WoodpeckerApi) -> WoodpeckerProvider {
+        set_token("WOODPECKER_TOKEN", "test-token");
+        let repo = WoodpeckerRepo::new("org/repo".to_string(), "main".to_string());
+        WoodpeckerProvider::new(
+            &server.url(),
+            api,
+            "WOODPECKER_TOKEN".to_string(),
+            vec![repo],
+        )
+    }
+
+    #[test]
+    fn reports_failure_pending_when_running_after_killed_pipeline() {
+        let mut server = Server::new();
+        let _lookup = server
+            .mock("GET", "/api/repos/lookup/org/repo")
+            .match_header("authorization", "Bearer test-token")
+            .with_status(200)
+            .with_body(r#"{"id":12,"full_name":"org/repo"}"#)
+            .create();
+        let _pipelines = server
+            .mock("GET", "/api/repos/12/pipelines")
+            .match_query(mockito::Matcher::UrlEncoded("branch".into(), "main".into()))
+            .match_header("authorization", "Bearer test-token")
+            .with_status(200)
+            .with_body(
+                r#"[
+                    {"number":9,"status":"running","event":"push","branch":"main","started":1735725600,"finished":0},
+                    {"number":8,"status":"success","event":"pull_request","branch":"main"},
+                    {"number":7,"status":"killed","event":"push","branch":"main"}
+                ]"#,
+            )
+            .create();
+
+        let statuses = poll_once(provider(&server, WoodpeckerApi::Woodpecker));
+
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].state, StateSummary::FailurePending);
+        assert_eq!(statuses[0].run_id, Some(9));
+        assert_eq!(
+            statuses[0].url,
+            Some(format!("{}/repos/12/pipeline/9", server.url()))
+        );
+        assert_eq!(
+            statuses[0].started_at.as_deref(),
+            Some("2025-01-01T10:00:00Z")
+        );
+    }
+
+    #[test]
+    fn reports_failure_from_drone_builds() {
+        let mut server = Server::new();
+        let _builds = mock_json(
+            &mut server,
+            "/api/repos/org/repo/builds",
+            r#"[{"number":4,"status":"error","event":"push","branch":"main"},{"number":3,"status":"success","event":"push","branch":"main"}]"#,
+        );
+
+        let statuses = poll_once(provider(&server, WoodpeckerApi::Drone));
+
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].state, StateSummary::Failure);
+        assert_eq!(
+            statuses[0].url,
+            Some(format!("{}/org/repo/4", server.url()))
+        );
+    }
+}
@@ -2,27 +2,45 @@ pub mod core;
 pub mod data_providers;
 mod tray_icon;
 
+mod check_config;
 mod cli_args;
 mod config_file;
+mod config_watcher;
+mod dashboard;
+mod notifications;
 mod setup;
+mod status_command;
 
-use crate::cli_args::load_cli_args;
+use crate::cli_args::{load_cli_args, Command};
 use crate::setup::setup;
 
-// Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
-#[tauri::command]
-fn greet(name: &str) -> String {
-    format!("Hello, {}! You've been greeted from Rust!", name)
-}
-
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     env_logger::init();
     let cli_args = load_cli_args();
+    if cli_args.print_schema {
+        println!("{}", config_file::config_schema());
+        return;
+    }
+    if let Some(Command::Status(status_args)) = &cli_args.command {
+        std::process::exit(status_command::print_status(&cli_args, status_args));
+    }
+    if cli_args.check_config {
+        std::process::exit(check_config::check_config(&cli_args));
+    }
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![greet])
+        .plugin(tauri_plugin_notification::init())
+        .invoke_handler(tauri::generate_handler![
+            dashboard::get_statuses,
+            dashboard::get_summary,
+            dashboard::get_rate_limits,
+            dashboard::get_config,
+            dashboard::get_config_error,
+            dashboard::open_config_file,
+            dashboard::refresh_now
+        ])
         .on_window_event(|window, event| {
             if let tauri::WindowEvent::CloseRequested { api, .. } = event {
                 let _ = window.hide();
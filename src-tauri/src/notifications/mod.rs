@@ -0,0 +1,32 @@
+use std::sync::Arc;
+
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::core::{NotificationController, Notifier, StateSummaryGateway};
+
+struct DesktopNotifier {
+    handle: AppHandle,
+}
+
+impl Notifier for DesktopNotifier {
+    fn notify(&self, title: &str, body: &str) {
+        if let Err(error) = self
+            .handle
+            .notification()
+            .builder()
+            .title(title)
+            .body(body)
+            .show()
+        {
+            eprintln!("Failed to show notification: {}", error);
+        }
+    }
+}
+
+pub fn setup_with(app: &AppHandle, dispatcher: &Arc<StateSummaryGateway>) {
+    let notifier = DesktopNotifier {
+        handle: app.clone(),
+    };
+    dispatcher.add_repo_controller(Box::new(NotificationController::new(Box::new(notifier))));
+}
@@ -1,30 +1,79 @@
 use crate::cli_args::CliArgs;
-use crate::config_file::read_config;
-use crate::core::{DataProvider, StateSummaryGateway};
-use crate::data_providers::providers_from_config;
+use crate::config_file::{read_config, write_schema, ConfigErrorReport};
+use crate::config_watcher;
+use crate::core::config::{create_default_config, Config};
+use crate::core::{ProviderSet, StateSummaryGateway};
+use crate::dashboard;
+use crate::data_providers::provider_from_config;
+use crate::notifications;
 use crate::tray_icon;
 use std::error::Error;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use tauri::{App, AppHandle, Manager};
 
+pub struct AppState {
+    pub(crate) gateway: Arc<StateSummaryGateway>,
+    pub(crate) providers: Mutex<ProviderSet>,
+    pub(crate) config_file: PathBuf,
+    pub(crate) config: Mutex<Config>,
+    pub(crate) config_error: Mutex<Option<ConfigErrorReport>>,
+}
+
+impl AppState {
+    /// Makes all running providers poll right away, the results arrive through the gateway.
+    pub(crate) fn refresh_providers(&self) {
+        if let Ok(providers) = self.providers.lock() {
+            providers.refresh();
+        }
+    }
+}
+
 pub fn setup(app: &mut App, cli_args: CliArgs) -> Result<(), Box<dyn std::error::Error + 'static>> {
     let app_handle = app.handle();
-    let config_file = resolve_config_file(&cli_args, &app_handle)?;
-    let app_handle = app_handle;
+    let config_file = resolve_config_file(&cli_args)?;
     if cli_args.edit_config {
         edit_config_file(app_handle, &config_file)?;
         return Ok(());
     }
-    let mut providers = load_providers(&config_file);
+    if let Err(error) = write_schema() {
+        log::error!("Failed to write the config schema: {}", error);
+    }
+    let (config, config_error) = match read_config(&config_file) {
+        Ok(config) => (config, None),
+        Err(error) => {
+            log::error!("Failed to load config.yaml: {}", error);
+            (
+                create_default_config(),
+                Some(ConfigErrorReport::from(&error)),
+            )
+        }
+    };
     let state_summary_gateway = Arc::new(StateSummaryGateway::new());
 
-    tray_icon::setup_with(&app_handle, &state_summary_gateway)?;
+    tray_icon::setup_with(app_handle, &state_summary_gateway, &config_file)?;
+    dashboard::setup_with(app_handle, &state_summary_gateway);
+    notifications::setup_with(app_handle, &state_summary_gateway);
 
-    for provider in &mut providers {
-        provider.start(state_summary_gateway.clone());
+    let mut providers = ProviderSet::new();
+    providers.start_missing(
+        &config.providers,
+        &state_summary_gateway,
+        provider_from_config,
+    );
+
+    app.manage(AppState {
+        gateway: state_summary_gateway,
+        providers: Mutex::new(providers),
+        config_file: config_file.clone(),
+        config: Mutex::new(config),
+        config_error: Mutex::new(None),
+    });
+    if config_error.is_some() {
+        config_watcher::set_config_error(app_handle, config_error);
     }
+    config_watcher::watch(app_handle.clone(), config_file);
 
     Ok(())
 }
@@ -33,37 +82,16 @@ fn edit_config_file(
     app_handle: &AppHandle,
     config_file: &PathBuf,
 ) -> Result<(), Box<dyn std::error::Error + 'static>> {
-    ensure_config_exists(&config_file)?;
-    open_editor(&config_file)?;
+    ensure_config_exists(config_file)?;
+    open_editor(config_file)?;
     app_handle.exit(0);
     Ok(())
 }
 
-fn resolve_config_file(args: &CliArgs, app_handle: &AppHandle) -> Result<PathBuf, Box<dyn Error>> {
-    Ok(match args.config_file.clone() {
-        Some(path) => path,
-        None => default_config_path(&app_handle)?,
-    })
-}
-
-fn load_providers(config_file: &PathBuf) -> Vec<Box<dyn DataProvider>> {
-    match read_config(config_file) {
-        Ok(config) => providers_from_config(&config),
-        Err(error) => {
-            log::error!("Failed to load config.yaml: {}", error);
-            Err(error).unwrap()
-        }
-    }
-}
-
-fn default_config_path(
-    app_handle: &tauri::AppHandle,
-) -> Result<PathBuf, Box<dyn std::error::Error + 'static>> {
-    let config_dir = match app_handle.path().config_dir() {
-        Ok(path) => path,
-        Err(e) => return Err(e.into()),
-    };
-    Ok(config_dir.join("config.yaml"))
+fn resolve_config_file(args: &CliArgs) -> Result<PathBuf, Box<dyn Error>> {
+    Ok(args
+        .config_file_or_default()
+        .ok_or("Could not determine the config directory")?)
 }
 
 fn ensure_config_exists(config_file: &PathBuf) -> Result<(), Box<dyn std::error::Error + 'static>> {
@@ -0,0 +1,177 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::cli_args::{CliArgs, StatusArgs};
+use crate::config_file::load_config;
+use crate::core::{combine_summaries, RepoStatus, StateSummary, StateSummaryGateway};
+use crate::data_providers::provider_from_config;
+
+/// Exit code when no status could be determined, e.g. because the config is broken.
+const EXIT_UNKNOWN: i32 = 4;
+
+#[derive(Serialize)]
+struct StatusReport {
+    summary: Option<StateSummary>,
+    repos: Vec<RepoStatus>,
+}
+
+/// Polls every configured repo once, prints the statuses and returns the process exit code.
+pub fn print_status(cli_args: &CliArgs, status_args: &StatusArgs) -> i32 {
+    let Some(config_file) = cli_args.config_file_or_default() else {
+        eprintln!("Could not determine the config directory");
+        return EXIT_UNKNOWN;
+    };
+    if !config_file.exists() {
+        eprintln!(
+            "{}: config file does not exist, start pmon once to create it or pass --config-file",
+            config_file.display()
+        );
+        return EXIT_UNKNOWN;
+    }
+    let config = match load_config(&config_file) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("{}: {}", config_file.display(), error);
+            return EXIT_UNKNOWN;
+        }
+    };
+
+    let gateway = StateSummaryGateway::new();
+    for provider_config in &config.providers {
+        provider_from_config(provider_config).poll_now(&gateway);
+    }
+    let repos = gateway.repo_statuses();
+    // Unlike the tray, the aggregate covers every repo, not only those shown in the tray.
+    let summary = repos.iter().fold(None, |combined, status| {
+        Some(combine_summaries(combined, status.state))
+    });
+
+    if status_args.json {
+        let report = StatusReport { summary, repos };
+        match serde_json::to_string_pretty(&report) {
+            Ok(json) => println!("{}", json),
+            Err(error) => {
+                eprintln!("Failed to serialize statuses: {}", error);
+                return EXIT_UNKNOWN;
+            }
+        }
+    } else {
+        print!("{}", status_table(&repos, Utc::now()));
+    }
+    exit_code(summary)
+}
+
+fn exit_code(summary: Option<StateSummary>) -> i32 {
+    match summary {
+        Some(StateSummary::Ok) => 0,
+        Some(StateSummary::Failure) => 1,
+        Some(StateSummary::OkPending) => 2,
+        Some(StateSummary::FailurePending) => 3,
+        Some(StateSummary::Error) | None => EXIT_UNKNOWN,
+    }
+}
+
+fn status_table(statuses: &[RepoStatus], now: DateTime<Utc>) -> String {
+    let header = ["STATE", "REPO", "BRANCH", "WORKFLOW", "AGE", "DETAIL"].map(String::from);
+    let rows: Vec<[String; 6]> = statuses
+        .iter()
+        .map(|status| {
+            [
+                state_label(status.state).to_string(),
+                status.repo.clone(),
+                if status.branch.is_empty() {
+                    "-".to_string()
+                } else {
+                    status.branch.clone()
+                },
+                status.workflow.clone().unwrap_or_else(|| "-".to_string()),
+                status
+                    .updated_at
+                    .as_deref()
+                    .or(status.started_at.as_deref())
+                    .and_then(|timestamp| age(timestamp, now))
+                    .unwrap_or_else(|| "-".to_string()),
+                status.detail.clone().unwrap_or_default(),
+            ]
+        })
+        .collect();
+
+    let mut widths = [0; 6];
+    for row in std::iter::once(&header).chain(&rows) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut table = String::new();
+    for row in std::iter::once(&header).chain(&rows) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        table.push_str(cells.join("  ").trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+fn state_label(state: StateSummary) -> &'static str {
+    match state {
+        StateSummary::Ok => "ok",
+        StateSummary::OkPending => "ok, running",
+        StateSummary::Failure => "failure",
+        StateSummary::FailurePending => "failure, running",
+        StateSummary::Error => "error",
+    }
+}
+
+/// How long ago an RFC 3339 timestamp was, in the largest whole unit.
+fn age(timestamp: &str, now: DateTime<Utc>) -> Option<String> {
+    let then = DateTime::parse_from_rfc3339(timestamp).ok()?;
+    let minutes = now.signed_duration_since(then).num_minutes().max(0);
+    Some(match minutes {
+        0 => "<1m".to_string(),
+        1..60 => format!("{}m", minutes),
+        60..1440 => format!("{}h", minutes / 60),
+        _ => format!("{}d", minutes / 1440),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+
+    use super::{exit_code, status_table};
+    use crate::core::{RepoStatus, StateSummary};
+
+    #[test]
+    fn table_lists_repos_with_age() {
+        let now: DateTime<Utc> = "2025-01-01T12:00:00Z".parse().unwrap();
+        let mut github = RepoStatus::new("github", "org/app", "main", StateSummary::Failure);
+        github.workflow = Some("ci.yml".to_string());
+        github.updated_at = Some("2025-01-01T09:30:00Z".to_string());
+        let mut gitlab = RepoStatus::new(
+            "gitlab",
+            "group/lib",
+            "develop",
+            StateSummary::FailurePending,
+        );
+        gitlab.detail = Some("lint (test) failed".to_string());
+
+        assert_eq!(
+            status_table(&[github, gitlab], now),
+            "STATE             REPO       BRANCH   WORKFLOW  AGE  DETAIL\n\
+             failure           org/app    main     ci.yml    2h\n\
+             failure, running  group/lib  develop  -         -    lint (test) failed\n"
+        );
+    }
+
+    #[test]
+    fn exit_code_reflects_summary() {
+        assert_eq!(exit_code(Some(StateSummary::Ok)), 0);
+        assert_eq!(exit_code(Some(StateSummary::Failure)), 1);
+        assert_eq!(exit_code(Some(StateSummary::Error)), 4);
+        assert_eq!(exit_code(None), 4);
+    }
+}
@@ -5,6 +5,8 @@ const SIZE: u32 = 32;
 const RED: [u8; 3] = [220, 50, 50];
 const GREEN: [u8; 3] = [46, 186, 86];
 const BLUE: [u8; 3] = [60, 120, 230];
+const ORANGE: [u8; 3] = [240, 150, 30];
+const GREY: [u8; 3] = [140, 140, 140];
 
 pub(super) fn tray_icon(state: StateSummary) -> Image<'static> {
     let (left, right, split) = match state {
@@ -12,10 +14,15 @@ pub(super) fn tray_icon(state: StateSummary) -> Image<'static> {
         StateSummary::Failure => (RED, RED, false),
         StateSummary::OkPending => (GREEN, BLUE, true),
         StateSummary::FailurePending => (RED, BLUE, true),
+        StateSummary::Error => (GREY, GREY, false),
     };
     create_maybe_split_tray_icon(left, right, split)
 }
 
+pub(super) fn config_error_icon() -> Image<'static> {
+    create_maybe_split_tray_icon(ORANGE, ORANGE, false)
+}
+
 fn create_maybe_split_tray_icon(left: [u8; 3], right: [u8; 3], split: bool) -> Image<'static> {
     let mut rgba = vec![0u8; (SIZE * SIZE * 4) as usize];
     let center = (SIZE as f32 - 1.0) / 2.0;
@@ -0,0 +1,132 @@
+use crate::core::{RepoStatus, StateSummary};
+use tauri::{
+    menu::{Menu, MenuItem, PredefinedMenuItem},
+    AppHandle, Manager, Wry,
+};
+use tauri_plugin_opener::OpenerExt;
+
+use super::TrayState;
+use crate::setup::AppState;
+
+const OPEN_WINDOW_ID: &str = "open-window";
+const REFRESH_ID: &str = "refresh";
+const EDIT_CONFIG_ID: &str = "edit-config";
+const EXIT_ID: &str = "exit";
+const OPEN_RUN_PREFIX: &str = "open-run:";
+
+pub(super) fn tray_menu(
+    app: &AppHandle,
+    statuses: &[RepoStatus],
+) -> Result<Menu<Wry>, tauri::Error> {
+    let menu = Menu::new(app)?;
+    menu.append(&MenuItem::with_id(
+        app,
+        OPEN_WINDOW_ID,
+        "Open window",
+        true,
+        None::<&str>,
+    )?)?;
+    if !statuses.is_empty() {
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+        for (index, status) in statuses.iter().enumerate() {
+            // Several entries may share a run url, so ids are indexes into the run urls.
+            menu.append(&MenuItem::with_id(
+                app,
+                format!("{}{}", OPEN_RUN_PREFIX, index),
+                repo_label(status),
+                status.url.is_some(),
+                None::<&str>,
+            )?)?;
+        }
+    }
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(
+        app,
+        REFRESH_ID,
+        "Refresh now",
+        true,
+        None::<&str>,
+    )?)?;
+    menu.append(&MenuItem::with_id(
+        app,
+        EDIT_CONFIG_ID,
+        "Edit config",
+        true,
+        None::<&str>,
+    )?)?;
+    menu.append(&MenuItem::with_id(
+        app,
+        EXIT_ID,
+        "Exit",
+        true,
+        None::<&str>,
+    )?)?;
+    Ok(menu)
+}
+
+pub(super) fn handle_menu_event(app: &AppHandle, id: &str) {
+    match id {
+        OPEN_WINDOW_ID => show_main_window(app),
+        REFRESH_ID => app.state::<AppState>().refresh_providers(),
+        EDIT_CONFIG_ID => {
+            let config_file = app.state::<TrayState>().config_file.clone();
+            if let Err(error) = app
+                .opener()
+                .open_path(config_file.to_string_lossy(), None::<&str>)
+            {
+                eprintln!("Failed to open {}: {}", config_file.display(), error);
+            }
+        }
+        EXIT_ID => app.exit(0),
+        _ => {
+            let Some(index) = id
+                .strip_prefix(OPEN_RUN_PREFIX)
+                .and_then(|index| index.parse::<usize>().ok())
+            else {
+                return;
+            };
+            let url = app
+                .state::<TrayState>()
+                .run_urls
+                .lock()
+                .ok()
+                .and_then(|run_urls| run_urls.get(index).cloned().flatten());
+            if let Some(url) = url
+                && let Err(error) = app.opener().open_url(&url, None::<&str>)
+            {
+                eprintln!("Failed to open {}: {}", url, error);
+            }
+        }
+    }
+}
+
+pub(super) fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Whether both statuses result in the same menu entry.
+pub(super) fn shown_alike(a: &RepoStatus, b: &RepoStatus) -> bool {
+    repo_label(a) == repo_label(b) && a.url.is_some() == b.url.is_some()
+}
+
+fn repo_label(status: &RepoStatus) -> String {
+    let label = format!("{} {}", state_emoji(status.state), status.title());
+    match &status.detail {
+        Some(detail) => format!("{}: {}", label, detail),
+        None => label,
+    }
+}
+
+fn state_emoji(state: StateSummary) -> &'static str {
+    match state {
+        StateSummary::Ok => "🟢",
+        StateSummary::Failure => "🔴",
+        StateSummary::OkPending => "🟢🔵",
+        StateSummary::FailurePending => "🔴🔵",
+        StateSummary::Error => "⚪",
+    }
+}
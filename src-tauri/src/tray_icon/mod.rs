@@ -1,14 +1,28 @@
-mod tray_icon;
+mod icon;
+mod menu;
 
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 
-use crate::core::{StateSummary, StateSummaryAdapter, StateSummaryGateway};
+use crate::core::{
+    RepoStatus, RepoStatusAdapter, StateSummary, StateSummaryAdapter, StateSummaryGateway,
+};
+use icon::{config_error_icon, tray_icon};
+use menu::{handle_menu_event, show_main_window, shown_alike, tray_menu};
 use tauri::{
-    menu::{Menu, MenuItem},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
     AppHandle, Manager,
 };
-use tray_icon::tray_icon;
+
+/// State shared by the tray controllers and the menu event handler.
+struct TrayState {
+    config_file: PathBuf,
+    summary: Mutex<Option<StateSummary>>,
+    config_error: AtomicBool,
+    /// The run URLs of the repo entries in the menu, indexed like their menu ids.
+    run_urls: Mutex<Vec<Option<String>>>,
+}
 
 struct TrayIconController {
     handle: AppHandle,
@@ -28,28 +42,135 @@ impl TrayIconController {
 
 impl StateSummaryAdapter for TrayIconController {
     fn set_state_summary(&self, state: StateSummary) {
-        let icon = tray_icon(state);
-        if let Some(tray) = self.handle.tray_by_id(&self.tray_id) {
-            if let Err(error) = tray.set_icon(Some(icon)) {
-                eprintln!("Failed to update tray icon: {}", error);
+        if let Ok(mut summary) = self.handle.state::<TrayState>().summary.lock() {
+            *summary = Some(state);
+        }
+        update_icon(&self.handle, &self.tray_id);
+    }
+}
+
+/// Shows the config error icon while the config is broken, the state summary otherwise.
+fn update_icon(app: &AppHandle, tray_id: &str) {
+    let tray_state = app.state::<TrayState>();
+    let icon = if tray_state.config_error.load(Ordering::Relaxed) {
+        config_error_icon()
+    } else {
+        let summary = tray_state.summary.lock().ok().and_then(|summary| *summary);
+        tray_icon(summary.unwrap_or(StateSummary::Ok))
+    };
+    if let Some(tray) = app.tray_by_id(tray_id)
+        && let Err(error) = tray.set_icon(Some(icon))
+    {
+        eprintln!("Failed to update tray icon: {}", error);
+    }
+}
+
+struct TrayMenuController {
+    handle: AppHandle,
+    tray_id: String,
+    statuses: Mutex<Vec<RepoStatus>>,
+}
+
+impl TrayMenuController {
+    fn new(handle: AppHandle, tray_id: impl Into<String>) -> Self {
+        Self {
+            handle,
+            tray_id: tray_id.into(),
+            statuses: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn store_run_urls(&self, statuses: &[RepoStatus]) {
+        if let Ok(mut run_urls) = self.handle.state::<TrayState>().run_urls.lock() {
+            *run_urls = statuses.iter().map(|status| status.url.clone()).collect();
+        }
+    }
+
+    fn update_menu(&self, statuses: &[RepoStatus]) {
+        let Some(tray) = self.handle.tray_by_id(&self.tray_id) else {
+            return;
+        };
+        self.store_run_urls(statuses);
+        if let Err(error) =
+            tray_menu(&self.handle, statuses).and_then(|menu| tray.set_menu(Some(menu)))
+        {
+            eprintln!("Failed to update tray menu: {}", error);
+        }
+    }
+}
+
+impl RepoStatusAdapter for TrayMenuController {
+    fn set_repo_status(&self, status: &RepoStatus) {
+        let Ok(mut statuses) = self.statuses.lock() else {
+            return;
+        };
+        let changed = match statuses
+            .iter_mut()
+            .find(|existing| existing.key() == status.key())
+        {
+            Some(existing) => {
+                let changed = !shown_alike(existing, status);
+                *existing = status.clone();
+                changed
             }
+            None => {
+                statuses.push(status.clone());
+                true
+            }
+        };
+        // Setting a menu closes it on some platforms, so unchanged polls leave it alone.
+        if changed {
+            self.update_menu(&statuses);
+        } else {
+            self.store_run_urls(&statuses);
         }
     }
+
+    fn remove_repo_status(&self, status: &RepoStatus) {
+        let Ok(mut statuses) = self.statuses.lock() else {
+            return;
+        };
+        statuses.retain(|existing| existing.key() != status.key());
+        self.update_menu(&statuses);
+    }
 }
 
 pub fn setup_with(
     app: &AppHandle,
     dispatcher: &Arc<StateSummaryGateway>,
+    config_file: &Path,
 ) -> Result<(), tauri::Error> {
+    app.manage(TrayState {
+        config_file: config_file.to_path_buf(),
+        summary: Mutex::new(None),
+        config_error: AtomicBool::new(false),
+        run_urls: Mutex::new(Vec::new()),
+    });
     setup_tray(app)?;
     dispatcher.add_controller(create_controller(app.clone()));
+    dispatcher.add_repo_controller(Box::new(TrayMenuController::new(app.clone(), TRAY_ICON_ID)));
     Ok(())
 }
 
+pub fn show_config_error(app: &AppHandle, error: Option<&str>) {
+    app.state::<TrayState>()
+        .config_error
+        .store(error.is_some(), Ordering::Relaxed);
+    update_icon(app, TRAY_ICON_ID);
+    let Some(tray) = app.tray_by_id(TRAY_ICON_ID) else {
+        return;
+    };
+    let tooltip = match error {
+        Some(error) => format!("pmon: {}", error),
+        None => "pmon".to_string(),
+    };
+    if let Err(error) = tray.set_tooltip(Some(tooltip)) {
+        eprintln!("Failed to update tray tooltip: {}", error);
+    }
+}
+
 fn setup_tray(app: &AppHandle) -> Result<(), tauri::Error> {
-    let open_window = MenuItem::with_id(app, "open-window", "Open window", true, None::<&str>)?;
-    let exit = MenuItem::with_id(app, "exit", "Exit", true, None::<&str>)?;
-    let menu = Menu::with_items(app, &[&open_window, &exit])?;
+    let menu = tray_menu(app, &[])?;
     let icon = tray_icon(StateSummary::Ok);
     let _tray = TrayIconBuilder::with_id(TRAY_ICON_ID)
         .icon(icon)
@@ -62,27 +183,10 @@ fn setup_tray(app: &AppHandle) -> Result<(), tauri::Error> {
                 ..
             } = event
             {
-                let app = tray.app_handle();
-                if let Some(window) = app.get_webview_window("main") {
-                    let _ = window.show();
-                    let _ = window.unminimize();
-                    let _ = window.set_focus();
-                }
-            }
-        })
-        .on_menu_event(|app, event| match event.id.as_ref() {
-            "open-window" => {
-                if let Some(window) = app.get_webview_window("main") {
-                    let _ = window.show();
-                    let _ = window.unminimize();
-                    let _ = window.set_focus();
-                }
-            }
-            "exit" => {
-                app.exit(0);
+                show_main_window(tray.app_handle());
             }
-            _ => {}
         })
+        .on_menu_event(|app, event| handle_menu_event(app, event.id.as_ref()))
         .build(app)?;
     Ok(())
 }